@@ -0,0 +1,242 @@
+//! Expected answers to check the solutions against, kept in `answers.toml`:
+//!
+//! ```toml
+//! [day01.input]
+//! part1 = "1319616"
+//! part2 = 27267728
+//! ```
+//!
+//! Only this subset of TOML is understood: a table per day and input file
+//! with a quoted or bare answer per part, comments and blank lines.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Where an answer belongs: the day, the name of the input file and the part.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    pub day: u8,
+    pub input: String,
+    pub part: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnswersError {
+    /// A line (1-based) that's neither a table, an answer, a comment nor blank.
+    Syntax { line: usize, text: String },
+    /// A table not named like `[day01.input]`.
+    InvalidTable { line: usize, name: String },
+    /// An answer outside of any table.
+    OutsideTable { line: usize },
+    /// A key other than `part1` and `part2`.
+    InvalidPart { line: usize, key: String },
+    /// A second answer for the same day, input and part.
+    Duplicate { line: usize },
+}
+
+impl Display for AnswersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswersError::Syntax { line, text } => write!(f, "line {line}: can't read {text:?}"),
+            AnswersError::InvalidTable { line, name } => {
+                write!(
+                    f,
+                    "line {line}: expected a table like [day01.input], found [{name}]"
+                )
+            }
+            AnswersError::OutsideTable { line } => {
+                write!(f, "line {line}: answer outside of a table")
+            }
+            AnswersError::InvalidPart { line, key } => {
+                write!(f, "line {line}: expected part1 or part2, found {key}")
+            }
+            AnswersError::Duplicate { line } => write!(f, "line {line}: answer given twice"),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+/// The expected answers, ordered by day, input and part.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    answers: BTreeMap<Key, String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        let mut answers = BTreeMap::new();
+        let mut table = None;
+
+        for (idx, line) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                let name = name.trim();
+                let parsed = name.split_once('.').and_then(|(day, input)| {
+                    let day = day.strip_prefix("day")?.parse::<u8>().ok()?;
+                    let input = input.trim_matches('"');
+
+                    (!input.is_empty()).then(|| (day, input.to_string()))
+                });
+
+                table = Some(parsed.ok_or_else(|| AnswersError::InvalidTable {
+                    line: line_no,
+                    name: name.to_string(),
+                })?);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(AnswersError::Syntax {
+                    line: line_no,
+                    text: line.to_string(),
+                });
+            };
+
+            let (day, input) = table
+                .clone()
+                .ok_or(AnswersError::OutsideTable { line: line_no })?;
+            let key = key.trim();
+            let part = match key {
+                "part1" => 1,
+                "part2" => 2,
+                _ => {
+                    return Err(AnswersError::InvalidPart {
+                        line: line_no,
+                        key: key.to_string(),
+                    })
+                }
+            };
+            let value = value.trim();
+            let value = match value
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+            {
+                Some(quoted) => quoted,
+                None if !value.is_empty() && !value.contains(['"', ' ']) => value,
+                None => {
+                    return Err(AnswersError::Syntax {
+                        line: line_no,
+                        text: line.to_string(),
+                    })
+                }
+            };
+
+            let key = Key { day, input, part };
+
+            if answers.insert(key, value.to_string()).is_some() {
+                return Err(AnswersError::Duplicate { line: line_no });
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub fn get(&self, day: u8, input: &str, part: u8) -> Option<&str> {
+        let key = Key {
+            day,
+            input: input.to_string(),
+            part,
+        };
+
+        self.answers.get(&key).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &str)> {
+        self.answers
+            .iter()
+            .map(|(key, answer)| (key, answer.as_str()))
+    }
+}
+
+/// The line up to a `#` outside of quotes.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..idx],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "\
+# real inputs
+[day01.input]
+part1 = \"1319616\"
+part2 = 27267728 # bare numbers work, too
+
+[day03.input_test2]
+part2 = \"48\"
+
+[ day10.\"input\" ]
+part1 = \"a#b\"
+";
+        let answers = Answers::parse(text).unwrap();
+
+        assert_eq!(answers.get(1, "input", 1), Some("1319616"));
+        assert_eq!(answers.get(1, "input", 2), Some("27267728"));
+        assert_eq!(answers.get(3, "input_test2", 2), Some("48"));
+        assert_eq!(answers.get(3, "input_test2", 1), None);
+        assert_eq!(answers.get(10, "input", 1), Some("a#b"));
+        assert_eq!(answers.iter().count(), 4);
+        assert_eq!(
+            answers.iter().next().map(|(key, _)| key.clone()),
+            Some(Key {
+                day: 1,
+                input: "input".to_string(),
+                part: 1
+            })
+        );
+
+        assert_eq!(Answers::parse(""), Ok(Answers::default()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text: &str| Answers::parse(text).unwrap_err();
+
+        assert_eq!(error("part1 = 1"), AnswersError::OutsideTable { line: 1 });
+        assert_eq!(
+            error("[day01]\npart1 = 1"),
+            AnswersError::InvalidTable {
+                line: 1,
+                name: "day01".to_string()
+            }
+        );
+        assert_eq!(
+            error("[day01.input]\npart3 = 1"),
+            AnswersError::InvalidPart {
+                line: 2,
+                key: "part3".to_string()
+            }
+        );
+        assert_eq!(
+            error("[day01.input]\npart1 = 1\n\npart1 = 2"),
+            AnswersError::Duplicate { line: 4 }
+        );
+        assert_eq!(
+            error("[day01.input]\npart1 = \"1").to_string(),
+            "line 2: can't read \"part1 = \\\"1\""
+        );
+        assert!(Answers::parse("[day01.input]\npart1").is_err());
+    }
+}
@@ -0,0 +1,494 @@
+//! Runs the solutions of all days: `aoc verify` checks them against the
+//! expected answers in `answers.toml`, `aoc bench` times them, `aoc fetch`
+//! downloads the puzzle inputs, `aoc submit` gives the answers and `aoc new`
+//! sets up another day.
+
+use std::{
+    hint::black_box,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use aoc2024::{
+    answers::Answers,
+    bench::{self, Counting, Report},
+    client::{self, Cache, Client, Config, Fetched, History, Known, Submitted, Throttle, Verdict},
+    runner::{self, Day, Status},
+    scaffold,
+};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting::new();
+
+// every day's binary, compiled in as a module to call its parts directly
+#[allow(dead_code)]
+#[path = "../day01/day01.rs"]
+mod day01;
+#[allow(dead_code)]
+#[path = "../day02/day02.rs"]
+mod day02;
+#[allow(dead_code)]
+#[path = "../day03/day03.rs"]
+mod day03;
+#[allow(dead_code)]
+#[path = "../day04/day04.rs"]
+mod day04;
+#[allow(dead_code)]
+#[path = "../day05/day05.rs"]
+mod day05;
+#[allow(dead_code)]
+#[path = "../day06/day06.rs"]
+mod day06;
+#[allow(dead_code)]
+#[path = "../day07/day07.rs"]
+mod day07;
+
+const DAYS: &[Day] = &[
+    Day {
+        number: 1,
+        parse: Some(|input| {
+            black_box(day01::build_lists(input));
+        }),
+        parts: [
+            Some(|input| day01::first_part(input).to_string()),
+            Some(|input| day01::second_part(input).to_string()),
+        ],
+    },
+    Day {
+        number: 2,
+        parse: Some(|input| {
+            black_box(day02::build_reports(input));
+        }),
+        parts: [
+            Some(|input| day02::first_part(input).to_string()),
+            Some(|input| day02::second_part(input).to_string()),
+        ],
+    },
+    Day {
+        number: 3,
+        parse: Some(|input| {
+            black_box(day03::parse(day03::tokenize(input)));
+        }),
+        parts: [
+            Some(|input| day03::first_part(input).to_string()),
+            Some(|input| day03::second_part(input).to_string()),
+        ],
+    },
+    Day {
+        number: 4,
+        parse: Some(|input| {
+            let _ = black_box(day04::build_grid(input));
+        }),
+        parts: [
+            Some(|input| day04::first_part(input).expect("a grid").to_string()),
+            Some(|input| day04::second_part(input).expect("a grid").to_string()),
+        ],
+    },
+    Day {
+        number: 5,
+        parse: Some(|input| {
+            let _ = black_box(day05::get_rules_and_updates(input));
+        }),
+        parts: [
+            Some(|input| day05::first_part(input).expect("a middle page").to_string()),
+            Some(|input| {
+                day05::second_part(input)
+                    .expect("a middle page")
+                    .to_string()
+            }),
+        ],
+    },
+    Day {
+        number: 6,
+        parse: Some(|input| {
+            let _ = black_box(day06::build_grid(input));
+        }),
+        parts: [
+            Some(|input| day06::first_part(input).expect("a grid").to_string()),
+            None,
+        ],
+    },
+    Day {
+        number: 7,
+        parse: Some(|input| {
+            black_box(day07::parse(input));
+        }),
+        parts: [
+            Some(|input| day07::first_part(input).to_string()),
+            Some(|input| day07::second_part(input).to_string()),
+        ],
+    },
+];
+
+const USAGE: &str = "\
+usage: aoc verify [--day <n>] [--answers <path>]
+       aoc bench [--day <n>] [--input <name>] [--runs <n>] [--save <path>]
+                 [--baseline <path>] [--threshold <percent>]
+       aoc fetch --day <n>
+       aoc submit --day <n> --part <n> [--answer <answer>]
+       aoc new --day <n>";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let result = match args.split_first() {
+        Some((command, args)) if command == "verify" => verify(root, args),
+        Some((command, args)) if command == "bench" => bench(root, args),
+        Some((command, args)) if command == "fetch" => fetch(root, args),
+        Some((command, args)) if command == "submit" => submit(root, args),
+        Some((command, args)) if command == "new" => new(root, args),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// The days picked with `--day <n>`, all of them if none is.
+fn select_days(day: Option<u8>) -> Result<Vec<Day>, String> {
+    match day {
+        None => Ok(DAYS.to_vec()),
+        Some(number) => DAYS
+            .iter()
+            .find(|day| day.number == number)
+            .map(|day| vec![*day])
+            .ok_or(format!("no solution for day {number}")),
+    }
+}
+
+/// Prints the table of checks, returning whether none failed.
+fn verify(root: &Path, args: &[String]) -> Result<bool, String> {
+    let mut day = None;
+    let mut path = root.join("answers.toml");
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+        match arg.as_str() {
+            "--day" => {
+                let number = value()?;
+                day = Some(
+                    number
+                        .parse()
+                        .map_err(|_| format!("invalid day: {number}"))?,
+                );
+            }
+            "--answers" => path = PathBuf::from(value()?),
+            other => return Err(format!("unknown argument: {other}\n{USAGE}")),
+        }
+    }
+
+    let text =
+        std::fs::read_to_string(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let answers = Answers::parse(&text).map_err(|err| format!("{}: {err}", path.display()))?;
+
+    // a panicking part is reported as failed, without the noise
+    std::panic::set_hook(Box::new(|_| {}));
+    let checks = runner::verify(&select_days(day)?, &answers, root)
+        .map_err(|err| format!("{}: {err}", root.display()))?;
+    let _ = std::panic::take_hook();
+
+    print!("{}", runner::table(&checks));
+
+    Ok(checks.iter().all(|check| check.status != Status::Fail))
+}
+
+/// Prints the timings, saving them if asked to, and returns whether none
+/// regressed beyond the threshold compared to the baseline.
+fn bench(root: &Path, args: &[String]) -> Result<bool, String> {
+    let mut day = None;
+    let mut input = "input".to_string();
+    let mut runs = 10;
+    let mut save = None;
+    let mut baseline = None;
+    let mut threshold = 10.0;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+        match arg.as_str() {
+            "--day" => {
+                let number = value()?;
+                day = Some(
+                    number
+                        .parse()
+                        .map_err(|_| format!("invalid day: {number}"))?,
+                );
+            }
+            "--input" => input = value()?.clone(),
+            "--runs" => {
+                let number = value()?;
+                runs = number
+                    .parse()
+                    .ok()
+                    .filter(|&runs| runs > 0)
+                    .ok_or(format!("invalid number of runs: {number}"))?;
+            }
+            "--save" => save = Some(PathBuf::from(value()?)),
+            "--baseline" => baseline = Some(PathBuf::from(value()?)),
+            "--threshold" => {
+                let percent = value()?;
+                threshold = percent
+                    .parse()
+                    .ok()
+                    .filter(|&threshold: &f64| threshold >= 0.0)
+                    .ok_or(format!("invalid threshold: {percent}"))?;
+            }
+            other => return Err(format!("unknown argument: {other}\n{USAGE}")),
+        }
+    }
+
+    // read before running, not to find out it's broken only afterwards
+    let baseline = baseline
+        .map(|path| {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+
+            Report::from_json(&text).map_err(|err| format!("{}: {err}", path.display()))
+        })
+        .transpose()?;
+
+    std::panic::set_hook(Box::new(|_| {}));
+    let report = bench::bench(&select_days(day)?, root, &input, runs, &ALLOCATOR)
+        .map_err(|err| format!("{}: {err}", root.display()))?;
+    let _ = std::panic::take_hook();
+
+    print!("{}", report.table(baseline.as_ref()));
+
+    if let Some(path) = save {
+        std::fs::write(&path, report.to_json())
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+
+    let regressions = baseline
+        .map(|baseline| bench::regressions(&baseline, &report, threshold))
+        .unwrap_or_default();
+
+    for regression in &regressions {
+        eprintln!("regression: {regression}");
+    }
+
+    Ok(regressions.is_empty())
+}
+
+/// `aoc.toml` in `root` if there is one, the environment taking precedence.
+fn load_config(root: &Path) -> Result<Config, String> {
+    let path = root.join("aoc.toml");
+    let config = match std::fs::read_to_string(&path) {
+        Ok(text) => Config::parse(&text).map_err(|err| format!("{}: {err}", path.display()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+
+    Ok(config.with_env(|key| std::env::var(key).ok()))
+}
+
+/// The client for the website along with the cache in `inputs`, where it
+/// also keeps when the next request may be sent.
+fn connect(root: &Path) -> Result<(Client, Cache), String> {
+    let config = load_config(root)?;
+    let cache = Cache::new(root.join("inputs"));
+    let throttle = Throttle::new(cache.dir().join(".last-request"), config.interval);
+
+    Ok((Client::new(config, throttle), cache))
+}
+
+/// The day of commands taking nothing but `--day <n>`.
+fn day_arg(args: &[String]) -> Result<u8, String> {
+    match args {
+        [flag, number] if flag == "--day" => number
+            .parse::<u8>()
+            .map_err(|_| format!("invalid day: {number}")),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Downloads the input of a day into `inputs`, unless it's there already,
+/// and puts a copy next to the day's code if that has no input yet.
+fn fetch(root: &Path, args: &[String]) -> Result<bool, String> {
+    let day = day_arg(args)?;
+
+    let (client, cache) = connect(root)?;
+
+    let fetched = match client::fetch(&client, &cache, day, SystemTime::now()) {
+        Ok(fetched) => fetched,
+        Err(err) => {
+            eprintln!("day {day}: {err}");
+            return Ok(false);
+        }
+    };
+
+    let path = fetched.path();
+    let shown = path.strip_prefix(root).unwrap_or(path).display();
+    match fetched {
+        Fetched::Cached(_) => println!("day {day}: cached in {shown}"),
+        Fetched::Downloaded(_) => println!("day {day}: downloaded to {shown}"),
+    }
+
+    let dir = root.join("src").join(format!("day{day:02}"));
+    let input = dir.join("input");
+
+    if dir.is_dir() && !input.exists() {
+        std::fs::copy(path, &input).map_err(|err| format!("{}: {err}", input.display()))?;
+        println!("day {day}: copied to src/day{day:02}/input");
+    }
+
+    Ok(true)
+}
+
+/// Gives the answer to a part, computed from the day's input unless it's
+/// given, returning whether it's right. The answers are kept in
+/// `inputs/submissions`, not to give a wrong one twice.
+fn submit(root: &Path, args: &[String]) -> Result<bool, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut answer = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+        match arg.as_str() {
+            "--day" => {
+                let number = value()?;
+                day = Some(
+                    number
+                        .parse::<u8>()
+                        .map_err(|_| format!("invalid day: {number}"))?,
+                );
+            }
+            "--part" => {
+                let number = value()?;
+                part = Some(
+                    number
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|part| (1..=2).contains(part))
+                        .ok_or(format!("invalid part: {number}"))?,
+                );
+            }
+            "--answer" => answer = Some(value()?.clone()),
+            other => return Err(format!("unknown argument: {other}\n{USAGE}")),
+        }
+    }
+
+    let (Some(day), Some(part)) = (day, part) else {
+        return Err(USAGE.to_string());
+    };
+
+    let answer = match answer {
+        Some(answer) => answer,
+        None => {
+            let solution = select_days(Some(day))?[0].parts[part as usize - 1]
+                .ok_or(format!("day {day} part {part} isn't solved yet"))?;
+            let path = root.join("src").join(format!("day{day:02}")).join("input");
+            let input = std::fs::read_to_string(&path)
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+
+            std::panic::set_hook(Box::new(|_| {}));
+            let answer = runner::run(solution, &input);
+            let _ = std::panic::take_hook();
+
+            answer.ok_or(format!("day {day} part {part} panicked"))?
+        }
+    };
+
+    let (client, cache) = connect(root)?;
+    let mut history = History::load(cache.dir().join("submissions"))
+        .map_err(|err| format!("{}: {err}", cache.dir().display()))?;
+
+    println!("day {day} part {part}: {answer}");
+
+    let submitted =
+        match client::submit(&client, &mut history, day, part, &answer, SystemTime::now()) {
+            Ok(submitted) => submitted,
+            Err(err) => {
+                eprintln!("day {day} part {part}: {err}");
+                return Ok(false);
+            }
+        };
+
+    let wait = |wait: Option<Duration>| match wait {
+        Some(wait) => format!(", wait {}s before the next answer", wait.as_secs()),
+        None => String::new(),
+    };
+
+    match submitted {
+        Submitted::Known(known) => {
+            println!("not submitted, {known}");
+            Ok(matches!(known, Known::Solved(right) if right == answer.trim()))
+        }
+        Submitted::Waiting(left) => {
+            println!("not submitted, wait {}s", left.as_secs());
+            Ok(false)
+        }
+        Submitted::Replied(reply) => {
+            let verdict = match reply.verdict {
+                Verdict::Right => "right answer",
+                Verdict::Wrong => "wrong answer",
+                Verdict::TooHigh => "wrong answer, too high",
+                Verdict::TooLow => "wrong answer, too low",
+                Verdict::TooSoon => "answered too recently",
+                Verdict::WrongLevel => "not judged, solved already or part 1 isn't yet",
+            };
+            println!("{verdict}{}", wait(reply.wait));
+
+            Ok(matches!(reply.verdict, Verdict::Right))
+        }
+    }
+}
+
+/// Sets up a day from the template with its inputs, the downloaded one if
+/// there is, and adds it to `Cargo.toml` and to the `aoc` command.
+fn new(root: &Path, args: &[String]) -> Result<bool, String> {
+    let day = day_arg(args)?;
+    scaffold::check_day(day).map_err(|err| err.to_string())?;
+
+    let name = format!("day{day:02}");
+    let dir = root.join("src").join(&name);
+
+    if dir.exists() {
+        return Err(format!("{} exists already", dir.display()));
+    }
+
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))
+    };
+    let write = |path: &Path, text: &str| {
+        std::fs::write(path, text).map_err(|err| format!("{}: {err}", path.display()))
+    };
+
+    // worked out before writing anything, not to leave a day half set up
+    let manifest = root.join("Cargo.toml");
+    let aoc = root.join("src").join("aoc").join("aoc.rs");
+    let new_manifest = scaffold::add_bin(&read(&manifest)?, day).map_err(|err| err.to_string())?;
+    let new_aoc = scaffold::add_to_aoc(&read(&aoc)?, day).map_err(|err| err.to_string())?;
+    let input =
+        std::fs::read_to_string(Cache::new(root.join("inputs")).path(day)).unwrap_or_default();
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    write(&dir.join(format!("{name}.rs")), scaffold::template())?;
+    write(&dir.join("input"), &input)?;
+    write(&dir.join("input_test"), "")?;
+    write(&manifest, &new_manifest)?;
+    write(&aoc, &new_aoc)?;
+
+    println!("created src/{name}/{name}.rs, src/{name}/input and src/{name}/input_test");
+    println!("added {name} to Cargo.toml and src/aoc/aoc.rs");
+
+    if input.is_empty() {
+        println!("fill in src/{name}/input or run `aoc fetch --day {day}`");
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,857 @@
+//! Times the stages of every day for the `aoc bench` command and keeps the
+//! results as JSON, so a later run can be compared against them:
+//!
+//! ```json
+//! {
+//!   "input": "input",
+//!   "runs": 10,
+//!   "results": [
+//!     {"day": 1, "stage": "parse", "min_ns": 61541, "median_ns": 63208, "p95_ns": 70125, "allocations": 14, "reallocations": 3, "bytes": 65520}
+//!   ]
+//! }
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Display,
+    hint::black_box,
+    io, panic,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::runner::Day;
+
+/// The system allocator counting what it's asked for, to be installed as the
+/// `#[global_allocator]` of a binary.
+///
+/// The counts are process-wide: whatever other threads allocate while a
+/// stage runs is counted along with it.
+#[derive(Debug, Default)]
+pub struct Counting {
+    allocations: AtomicU64,
+    reallocations: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counting {
+    pub const fn new() -> Self {
+        Self {
+            allocations: AtomicU64::new(0),
+            reallocations: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Everything allocated so far.
+    pub fn snapshot(&self) -> Allocations {
+        Allocations {
+            count: self.allocations.load(Ordering::Relaxed),
+            reallocations: self.reallocations.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size as u64, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    // growing a vector only adds what it grew by
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let grown = new_size.saturating_sub(layout.size());
+        self.reallocations.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(grown as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocations {
+    pub count: u64,
+    /// Blocks resized, on top of `count`.
+    pub reallocations: u64,
+    /// Allocated, or added by resizing.
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Parse,
+    FirstPart,
+    SecondPart,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::FirstPart => "part1",
+            Stage::SecondPart => "part2",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Stage::Parse, Stage::FirstPart, Stage::SecondPart]
+            .into_iter()
+            .find(|stage| stage.name() == name)
+    }
+}
+
+/// Timings of a stage over all runs, `p95` being the nearest-rank percentile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    /// `None` without any timings.
+    pub fn of(timings: &[Duration]) -> Option<Self> {
+        let mut sorted = timings.to_vec();
+        sorted.sort();
+
+        let rank = |percent: usize| sorted[(sorted.len() * percent).div_ceil(100).max(1) - 1];
+
+        (!sorted.is_empty()).then(|| Stats {
+            min: sorted[0],
+            median: rank(50),
+            p95: rank(95),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub stage: Stage,
+    pub stats: Stats,
+    /// Allocated per run.
+    pub allocations: Allocations,
+}
+
+/// Runs `stage` once to warm up and then `runs` times, timing each run and
+/// averaging the allocations, those of other threads meanwhile included.
+/// `None` if the stage panics.
+pub fn measure(stage: impl Fn(), runs: usize, counter: &Counting) -> Option<(Stats, Allocations)> {
+    panic::catch_unwind(panic::AssertUnwindSafe(&stage)).ok()?;
+
+    // allocated up front, so that only the stage is counted
+    let mut timings = Vec::with_capacity(runs);
+    let before = counter.snapshot();
+
+    for _ in 0..runs {
+        let start = Instant::now();
+        stage();
+        timings.push(start.elapsed());
+    }
+
+    let after = counter.snapshot();
+
+    let runs = runs.max(1) as u64;
+    let allocations = Allocations {
+        count: (after.count - before.count) / runs,
+        reallocations: (after.reallocations - before.reallocations) / runs,
+        bytes: (after.bytes - before.bytes) / runs,
+    };
+
+    Some((Stats::of(&timings)?, allocations))
+}
+
+/// The results of a benchmark, as saved to compare with a later one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Name of the input file the days were run on.
+    pub input: String,
+    pub runs: usize,
+    pub measurements: Vec<Measurement>,
+}
+
+/// Measures every stage a day has on its `input` file, skipping days
+/// without one as well as stages that panic.
+pub fn bench(
+    days: &[Day],
+    root: &std::path::Path,
+    input: &str,
+    runs: usize,
+    counter: &Counting,
+) -> io::Result<Report> {
+    let mut measurements = Vec::new();
+
+    for day in days {
+        let path = day.dir(root).join(input);
+
+        if !path.exists() {
+            continue;
+        }
+
+        let text = std::fs::read_to_string(path)?;
+        let [first, second] = day.parts;
+        let stages = [
+            (Stage::Parse, day.parse.map(|parse| timed(parse, &text))),
+            (Stage::FirstPart, first.map(|part| timed(part, &text))),
+            (Stage::SecondPart, second.map(|part| timed(part, &text))),
+        ];
+
+        for (stage, run) in stages {
+            let Some((stats, allocations)) = run.and_then(|run| measure(run, runs, counter)) else {
+                continue;
+            };
+
+            measurements.push(Measurement {
+                day: day.number,
+                stage,
+                stats,
+                allocations,
+            });
+        }
+    }
+
+    Ok(Report {
+        input: input.to_string(),
+        runs,
+        measurements,
+    })
+}
+
+/// Runs `stage` on `text`, keeping the compiler from optimising either away.
+fn timed<T: 'static>(stage: fn(&str) -> T, text: &str) -> Box<dyn Fn() + '_> {
+    Box::new(move || {
+        black_box(stage(black_box(text)));
+    })
+}
+
+/// A stage whose median got slower than the baseline's by more than the threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regression {
+    pub day: u8,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {} {} median {:.1?} -> {:.1?} ({})",
+            self.day,
+            self.stage.name(),
+            self.baseline,
+            self.current,
+            change(self.baseline, self.current)
+        )
+    }
+}
+
+/// The stages measured in both reports whose median grew by more than
+/// `threshold` percent.
+pub fn regressions(baseline: &Report, current: &Report, threshold: f64) -> Vec<Regression> {
+    current
+        .measurements
+        .iter()
+        .filter_map(|measurement| {
+            let before = baseline.find(measurement.day, measurement.stage)?;
+            let (baseline, current) = (before.stats.median, measurement.stats.median);
+
+            (current.as_secs_f64() > baseline.as_secs_f64() * (1.0 + threshold / 100.0)).then_some(
+                Regression {
+                    day: measurement.day,
+                    stage: measurement.stage,
+                    baseline,
+                    current,
+                },
+            )
+        })
+        .collect()
+}
+
+/// The relative change from `before` to `after`, e.g. `+12.5%`.
+fn change(before: Duration, after: Duration) -> String {
+    if before.is_zero() {
+        return "-".to_string();
+    }
+
+    let percent = (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0;
+    format!("{percent:+.1}%")
+}
+
+impl Report {
+    pub fn find(&self, day: u8, stage: Stage) -> Option<&Measurement> {
+        self.measurements
+            .iter()
+            .find(|measurement| measurement.day == day && measurement.stage == stage)
+    }
+
+    /// Lays the measurements out as a table, with the change of the median
+    /// from the `baseline` if there is one.
+    pub fn table(&self, baseline: Option<&Report>) -> String {
+        let mut header = [
+            "day", "stage", "min", "median", "p95", "allocs", "reallocs", "bytes",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        if baseline.is_some() {
+            header.push("change".to_string());
+        }
+
+        let rows = self.measurements.iter().map(|measurement| {
+            let mut row = vec![
+                measurement.day.to_string(),
+                measurement.stage.name().to_string(),
+                format!("{:.1?}", measurement.stats.min),
+                format!("{:.1?}", measurement.stats.median),
+                format!("{:.1?}", measurement.stats.p95),
+                measurement.allocations.count.to_string(),
+                measurement.allocations.reallocations.to_string(),
+                measurement.allocations.bytes.to_string(),
+            ];
+
+            if let Some(baseline) = baseline {
+                row.push(
+                    baseline
+                        .find(measurement.day, measurement.stage)
+                        .map(|before| change(before.stats.median, measurement.stats.median))
+                        .unwrap_or("-".to_string()),
+                );
+            }
+
+            row
+        });
+        let rows = std::iter::once(header).chain(rows).collect::<Vec<_>>();
+
+        let mut widths = vec![0; rows[0].len()];
+
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+
+        for row in &rows {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(idx, (cell, &width))| match idx {
+                    1 => format!("{cell:<width$}"),
+                    _ => format!("{cell:>width$}"),
+                })
+                .collect::<Vec<_>>();
+
+            out += cells.join("  ").trim_end();
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let results = self
+            .measurements
+            .iter()
+            .map(|measurement| {
+                format!(
+                    "    {{\"day\": {}, \"stage\": \"{}\", \"min_ns\": {}, \"median_ns\": {}, \"p95_ns\": {}, \"allocations\": {}, \"reallocations\": {}, \"bytes\": {}}}",
+                    measurement.day,
+                    measurement.stage.name(),
+                    measurement.stats.min.as_nanos(),
+                    measurement.stats.median.as_nanos(),
+                    measurement.stats.p95.as_nanos(),
+                    measurement.allocations.count,
+                    measurement.allocations.reallocations,
+                    measurement.allocations.bytes,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{{\n  \"input\": {:?},\n  \"runs\": {},\n  \"results\": [\n{}\n  ]\n}}\n",
+            self.input,
+            self.runs,
+            results.join(",\n")
+        )
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ReportError> {
+        let json = Json::parse(text)?;
+
+        let input = match json.get("input") {
+            Some(Json::String(input)) => input.clone(),
+            _ => {
+                return Err(ReportError::InvalidField {
+                    result: None,
+                    field: "input",
+                })
+            }
+        };
+        let runs = json
+            .get("runs")
+            .and_then(Json::as_u64)
+            .ok_or(ReportError::InvalidField {
+                result: None,
+                field: "runs",
+            })?;
+        let Some(Json::Array(results)) = json.get("results") else {
+            return Err(ReportError::InvalidField {
+                result: None,
+                field: "results",
+            });
+        };
+
+        let measurements = results
+            .iter()
+            .enumerate()
+            .map(|(idx, result)| {
+                let number = |field| {
+                    result
+                        .get(field)
+                        .and_then(Json::as_u64)
+                        .ok_or(ReportError::InvalidField {
+                            result: Some(idx),
+                            field,
+                        })
+                };
+                let nanos = |field| number(field).map(Duration::from_nanos);
+
+                let day = number("day")?
+                    .try_into()
+                    .map_err(|_| ReportError::InvalidField {
+                        result: Some(idx),
+                        field: "day",
+                    })?;
+                let stage = match result.get("stage") {
+                    Some(Json::String(name)) => {
+                        Stage::from_name(name).ok_or_else(|| ReportError::UnknownStage {
+                            result: idx,
+                            stage: name.clone(),
+                        })?
+                    }
+                    _ => {
+                        return Err(ReportError::InvalidField {
+                            result: Some(idx),
+                            field: "stage",
+                        })
+                    }
+                };
+
+                Ok(Measurement {
+                    day,
+                    stage,
+                    stats: Stats {
+                        min: nanos("min_ns")?,
+                        median: nanos("median_ns")?,
+                        p95: nanos("p95_ns")?,
+                    },
+                    allocations: Allocations {
+                        count: number("allocations")?,
+                        // not in reports from before it was counted
+                        reallocations: match result.get("reallocations") {
+                            Some(_) => number("reallocations")?,
+                            None => 0,
+                        },
+                        bytes: number("bytes")?,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Report {
+            input,
+            runs: runs as usize,
+            measurements,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportError {
+    /// Not JSON, reading stopped at the byte offset.
+    Syntax {
+        offset: usize,
+    },
+    /// A field missing or of the wrong type, in the report itself or in one
+    /// of its results (0-based).
+    InvalidField {
+        result: Option<usize>,
+        field: &'static str,
+    },
+    UnknownStage {
+        result: usize,
+        stage: String,
+    },
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::Syntax { offset } => write!(f, "invalid JSON at byte {offset}"),
+            ReportError::InvalidField {
+                result: None,
+                field,
+            } => {
+                write!(f, "missing or invalid {field:?}")
+            }
+            ReportError::InvalidField {
+                result: Some(result),
+                field,
+            } => write!(f, "result {result}: missing or invalid {field:?}"),
+            ReportError::UnknownStage { result, stage } => {
+                write!(f, "result {result}: unknown stage {stage:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+/// Just enough JSON to read back a report.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, ReportError> {
+        let mut parser = JsonParser { text, offset: 0 };
+        let json = parser.value()?;
+
+        parser.skip_whitespace();
+        match parser.offset == text.len() {
+            true => Ok(json),
+            false => Err(parser.error()),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Json::Number(n) if n >= 0.0 && n.fract() == 0.0 && n <= u64::MAX as f64 => {
+                Some(n as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+struct JsonParser<'t> {
+    text: &'t str,
+    offset: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self) -> ReportError {
+        ReportError::Syntax {
+            offset: self.offset,
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.text[self.offset..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+
+        if found {
+            self.offset += token.len();
+        }
+
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ReportError> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error()),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ReportError> {
+        self.skip_whitespace();
+
+        if self.eat("null") {
+            Ok(Json::Null)
+        } else if self.eat("true") {
+            Ok(Json::Bool(true))
+        } else if self.eat("false") {
+            Ok(Json::Bool(false))
+        } else if self.rest().starts_with('"') {
+            self.string().map(Json::String)
+        } else if self.eat("[") {
+            let values = self.sequence("]", Self::value)?;
+            Ok(Json::Array(values))
+        } else if self.eat("{") {
+            let fields = self.sequence("}", |parser| {
+                let key = parser.string()?;
+                parser.expect(":")?;
+                Ok((key, parser.value()?))
+            })?;
+            Ok(Json::Object(fields))
+        } else {
+            self.number()
+        }
+    }
+
+    /// Comma separated items up to the `end`, the opening bracket eaten already.
+    fn sequence<T>(
+        &mut self,
+        end: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ReportError>,
+    ) -> Result<Vec<T>, ReportError> {
+        let mut items = Vec::new();
+
+        if self.eat(end) {
+            return Ok(items);
+        }
+
+        loop {
+            items.push(item(self)?);
+
+            if self.eat(end) {
+                return Ok(items);
+            }
+
+            self.expect(",")?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ReportError> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.offset += idx + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => break,
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+
+        Err(self.error())
+    }
+
+    fn number(&mut self) -> Result<Json, ReportError> {
+        let len = self
+            .rest()
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(self.rest().len());
+        let number = self.rest()[..len].parse().map_err(|_| self.error())?;
+
+        self.offset += len;
+        Ok(Json::Number(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn measurement(day: u8, stage: Stage, median: u64) -> Measurement {
+        Measurement {
+            day,
+            stage,
+            stats: Stats {
+                min: ms(median - 1),
+                median: ms(median),
+                p95: ms(median + 2),
+            },
+            allocations: Allocations {
+                count: 3,
+                reallocations: 1,
+                bytes: 1024,
+            },
+        }
+    }
+
+    fn report(measurements: Vec<Measurement>) -> Report {
+        Report {
+            input: "input".to_string(),
+            runs: 10,
+            measurements,
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let timings = (1..=20).rev().map(ms).collect::<Vec<_>>();
+        assert_eq!(
+            Stats::of(&timings),
+            Some(Stats {
+                min: ms(1),
+                median: ms(10),
+                p95: ms(19),
+            })
+        );
+
+        assert_eq!(
+            Stats::of(&[ms(4)]),
+            Some(Stats {
+                min: ms(4),
+                median: ms(4),
+                p95: ms(4),
+            })
+        );
+        assert_eq!(Stats::of(&[]), None);
+    }
+
+    #[test]
+    fn test_counting() {
+        let counter = Counting::new();
+        let layout = Layout::from_size_align(64, 8).unwrap();
+
+        unsafe {
+            let ptr = counter.alloc(layout);
+            let ptr = counter.realloc(ptr, layout, 128);
+            let ptr = counter.realloc(ptr, Layout::from_size_align(128, 8).unwrap(), 32);
+            counter.dealloc(ptr, Layout::from_size_align(32, 8).unwrap());
+        }
+
+        // the block grew by 64 bytes, shrinking it added nothing
+        assert_eq!(
+            counter.snapshot(),
+            Allocations {
+                count: 1,
+                reallocations: 2,
+                bytes: 128
+            }
+        );
+
+        assert!(measure(|| panic!("unsolved"), 3, &counter).is_none());
+        let (_, allocations) = measure(|| {}, 3, &counter).unwrap();
+        assert_eq!(allocations, Allocations::default());
+    }
+
+    #[test]
+    fn test_json() {
+        let report = report(vec![
+            measurement(1, Stage::Parse, 2),
+            measurement(5, Stage::SecondPart, 30),
+        ]);
+        let json = report.to_json();
+
+        assert!(json.contains(
+            "{\"day\": 5, \"stage\": \"part2\", \"min_ns\": 29000000, \"median_ns\": 30000000, \"p95_ns\": 32000000, \"allocations\": 3, \"reallocations\": 1, \"bytes\": 1024}"
+        ));
+        assert_eq!(Report::from_json(&json), Ok(report));
+
+        let before = json.replace(", \"reallocations\": 1", "");
+        let before = Report::from_json(&before).unwrap();
+        assert_eq!(before.measurements[0].allocations.reallocations, 0);
+
+        assert_eq!(
+            Report::from_json("{\"input\": \"input\", \"runs\": 1, \"results\": [}"),
+            Err(ReportError::Syntax { offset: 42 })
+        );
+        assert_eq!(
+            Report::from_json("{\"input\": \"input\", \"results\": []}"),
+            Err(ReportError::InvalidField {
+                result: None,
+                field: "runs"
+            })
+        );
+        assert_eq!(
+            Report::from_json(
+                "{\"input\": \"in\\\"put\", \"runs\": 1, \"results\": [{\"day\": 1, \"stage\": \"part3\"}]}"
+            ),
+            Err(ReportError::UnknownStage {
+                result: 0,
+                stage: "part3".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_regressions() {
+        let baseline = report(vec![
+            measurement(1, Stage::Parse, 10),
+            measurement(1, Stage::FirstPart, 10),
+            measurement(2, Stage::FirstPart, 10),
+        ]);
+        let current = report(vec![
+            measurement(1, Stage::Parse, 11),
+            measurement(1, Stage::FirstPart, 12),
+            measurement(3, Stage::FirstPart, 50),
+        ]);
+
+        let regressions = regressions(&baseline, &current, 10.0);
+        assert_eq!(
+            regressions,
+            vec![Regression {
+                day: 1,
+                stage: Stage::FirstPart,
+                baseline: ms(10),
+                current: ms(12),
+            }]
+        );
+        assert_eq!(
+            regressions[0].to_string(),
+            "day 1 part1 median 10.0ms -> 12.0ms (+20.0%)"
+        );
+
+        let expected = "\
+day  stage     min  median     p95  allocs  reallocs  bytes  change
+  1  parse  10.0ms  11.0ms  13.0ms       3         1   1024  +10.0%
+  1  part1  11.0ms  12.0ms  14.0ms       3         1   1024  +20.0%
+  3  part1  49.0ms  50.0ms  52.0ms       3         1   1024       -
+";
+        assert_eq!(current.table(Some(&baseline)), expected);
+    }
+}
@@ -0,0 +1,1234 @@
+//! Talks to the Advent of Code website for `aoc fetch` and `aoc submit`,
+//! downloading each puzzle input once into a cache, keeping a history of the
+//! answers given and pacing the requests it sends.
+//!
+//! The session token and the rest of the settings are read from `aoc.toml`:
+//!
+//! ```toml
+//! session = "53616c7465645f5f..."  # or set AOC_SESSION
+//! base_url = "https://adventofcode.com"  # or set AOC_BASE_URL
+//! interval = 5  # seconds at least between two requests
+//! ```
+
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::answers::strip_comment;
+
+pub const YEAR: u16 = 2024;
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+/// When the first puzzle unlocked, 2024-12-01 at midnight EST.
+const FIRST_UNLOCK: u64 = 1_733_029_200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub session: Option<String>,
+    pub base_url: String,
+    /// The least time to leave between two requests.
+    pub interval: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            session: None,
+            base_url: BASE_URL.to_string(),
+            interval: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A line (1-based) that's neither a setting, a comment nor blank.
+    Syntax {
+        line: usize,
+        text: String,
+    },
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    InvalidInterval {
+        line: usize,
+        value: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Syntax { line, text } => write!(f, "line {line}: can't read {text:?}"),
+            ConfigError::UnknownKey { line, key } => write!(f, "line {line}: unknown key {key}"),
+            ConfigError::InvalidInterval { line, value } => {
+                write!(f, "line {line}: expected seconds, found {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let syntax = || ConfigError::Syntax {
+                line: line_no,
+                text: line.to_string(),
+            };
+            let (key, value) = line.split_once('=').ok_or_else(syntax)?;
+            let value = value.trim();
+            let value = match value
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+            {
+                Some(quoted) => quoted,
+                None if !value.is_empty() && !value.contains(['"', ' ']) => value,
+                None => return Err(syntax()),
+            };
+
+            match key.trim() {
+                "session" => config.session = Some(value.to_string()),
+                "base_url" => config.base_url = value.trim_end_matches('/').to_string(),
+                "interval" => {
+                    config.interval = value.parse().map(Duration::from_secs).map_err(|_| {
+                        ConfigError::InvalidInterval {
+                            line: line_no,
+                            value: value.to_string(),
+                        }
+                    })?
+                }
+                key => {
+                    return Err(ConfigError::UnknownKey {
+                        line: line_no,
+                        key: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Takes the session and base URL from `AOC_SESSION` and `AOC_BASE_URL`
+    /// over the ones configured, looking them up with `var`.
+    pub fn with_env(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(session) = var("AOC_SESSION").filter(|session| !session.is_empty()) {
+            self.session = Some(session);
+        }
+
+        if let Some(base_url) = var("AOC_BASE_URL").filter(|url| !url.is_empty()) {
+            self.base_url = base_url.trim_end_matches('/').to_string();
+        }
+
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// Not one of the 25 days.
+    InvalidDay(u8),
+    /// Not part 1 or 2.
+    InvalidPart(u8),
+    /// Empty or with a tab or line break, which the history can't keep.
+    InvalidAnswer(String),
+    Locked {
+        day: u8,
+        unlocks_in: Duration,
+    },
+    NoSession,
+    /// Too many requests, with the seconds to wait if the server said so.
+    RateLimited {
+        retry_after: Option<u64>,
+    },
+    /// Any other status but 200, e.g. 400 for an expired session.
+    Status {
+        status: u16,
+        body: String,
+    },
+    /// A reply to an answer that isn't understood, as text.
+    UnknownReply(String),
+    /// The server couldn't be reached or the response not read.
+    Http(String),
+    Io(io::Error),
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::InvalidDay(day) => write!(f, "there's no day {day}"),
+            ClientError::InvalidPart(part) => write!(f, "there's no part {part}"),
+            ClientError::InvalidAnswer(answer) => write!(f, "{answer:?} can't be an answer"),
+            ClientError::Locked { day, unlocks_in } => write!(
+                f,
+                "day {day} unlocks in {}h {}m",
+                unlocks_in.as_secs() / 3600,
+                unlocks_in.as_secs() / 60 % 60
+            ),
+            ClientError::NoSession => {
+                write!(
+                    f,
+                    "no session token, set AOC_SESSION or session in aoc.toml"
+                )
+            }
+            ClientError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            ClientError::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(f, "rate limited, retry after {seconds}s"),
+            ClientError::Status { status, body } => {
+                write!(f, "the server answered {status}")?;
+
+                match body.lines().next().map(str::trim) {
+                    Some(line) if !line.is_empty() => write!(f, ": {line}"),
+                    _ => Ok(()),
+                }
+            }
+            ClientError::UnknownReply(text) => write!(f, "unknown reply: {text}"),
+            ClientError::Http(err) => write!(f, "{err}"),
+            ClientError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+/// When the puzzle of the `day`, one of the 25, unlocks.
+fn unlocks(day: u8) -> SystemTime {
+    let days = u64::from(day.clamp(1, 25)) - 1;
+
+    UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + days * 24 * 60 * 60)
+}
+
+/// Keeps requests `interval` apart, or as long as the server asked for,
+/// across runs too, by recording when the next one may be sent in the
+/// `stamp` file.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    stamp: PathBuf,
+    interval: Duration,
+}
+
+impl Throttle {
+    pub fn new(stamp: PathBuf, interval: Duration) -> Self {
+        Self { stamp, interval }
+    }
+
+    /// How long to wait at `now` if the next request may be sent at `next`.
+    pub fn delay(&self, next: Option<SystemTime>, now: SystemTime) -> Duration {
+        next.and_then(|next| next.duration_since(now).ok())
+            .unwrap_or_default()
+    }
+
+    /// When the next request may be sent, as recorded.
+    pub fn next(&self) -> Option<SystemTime> {
+        std::fs::read_to_string(&self.stamp)
+            .ok()
+            .and_then(|millis| millis.trim().parse().ok())
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    /// Sleeps until the next request may be sent, recording it as sent.
+    pub fn wait(&self) -> io::Result<()> {
+        std::thread::sleep(self.delay(self.next(), SystemTime::now()));
+
+        self.record(SystemTime::now() + self.interval)
+    }
+
+    /// Holds the next request back until `retry_after` from `now`, as asked
+    /// for by a server limiting the rate.
+    pub fn back_off(&self, retry_after: Duration, now: SystemTime) -> io::Result<()> {
+        let next = now + retry_after;
+
+        match self.next() {
+            Some(recorded) if recorded >= next => Ok(()),
+            _ => self.record(next),
+        }
+    }
+
+    fn record(&self, next: SystemTime) -> io::Result<()> {
+        let millis = next.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        if let Some(dir) = self.stamp.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.stamp, millis.as_millis().to_string())
+    }
+}
+
+pub struct Client {
+    agent: ureq::Agent,
+    config: Config,
+    throttle: Throttle,
+}
+
+impl Client {
+    pub fn new(config: Config, throttle: Throttle) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .user_agent(concat!("aoc2024/", env!("CARGO_PKG_VERSION")))
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .new_agent();
+
+        Self {
+            agent,
+            config,
+            throttle,
+        }
+    }
+
+    /// Downloads the puzzle input of the `day`.
+    pub fn input(&self, day: u8) -> Result<String, ClientError> {
+        self.request(&format!("/{YEAR}/day/{day}/input"), None)
+    }
+
+    /// Gives the `answer` to a `part` of the `day`.
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> Result<Reply, ClientError> {
+        let level = part.to_string();
+        let page = self.request(
+            &format!("/{YEAR}/day/{day}/answer"),
+            Some([("level", &level), ("answer", answer)]),
+        )?;
+
+        Reply::parse(&page).ok_or_else(|| ClientError::UnknownReply(article(&page)))
+    }
+
+    /// Gets the page at `path`, or posts the `form` to it.
+    fn request(&self, path: &str, form: Option<[(&str, &str); 2]>) -> Result<String, ClientError> {
+        let session = self.config.session.as_ref().ok_or(ClientError::NoSession)?;
+        self.throttle.wait()?;
+
+        let url = format!("{}{path}", self.config.base_url);
+        let cookie = format!("session={session}");
+        let response = match form {
+            None => self.agent.get(url).header("Cookie", cookie).call(),
+            Some(form) => self
+                .agent
+                .post(url)
+                .header("Cookie", cookie)
+                .send_form(form),
+        };
+        let mut response = response.map_err(|err| ClientError::Http(err.to_string()))?;
+
+        let status = response.status().as_u16();
+        let retry_after: Option<u64> = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+
+        if let Some(seconds) = retry_after.filter(|_| status == 429) {
+            self.throttle
+                .back_off(Duration::from_secs(seconds), SystemTime::now())?;
+        }
+
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|err| ClientError::Http(err.to_string()))?;
+
+        match status {
+            200 => Ok(body),
+            429 => Err(ClientError::RateLimited { retry_after }),
+            status => Err(ClientError::Status { status, body }),
+        }
+    }
+}
+
+/// The downloaded inputs, one file per day like `day01`.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{day:02}"))
+    }
+
+    /// Stores the input of the `day`, never leaving a partial file behind.
+    pub fn put(&self, day: u8, input: &str) -> io::Result<PathBuf> {
+        let path = self.path(day);
+        let partial = path.with_extension("partial");
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(&partial, input)?;
+        std::fs::rename(&partial, &path)?;
+
+        Ok(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+impl Fetched {
+    pub fn path(&self) -> &Path {
+        match self {
+            Fetched::Cached(path) | Fetched::Downloaded(path) => path,
+        }
+    }
+}
+
+/// The cached input of the `day`, downloading it at `now` only if there's
+/// none yet and the puzzle has unlocked.
+pub fn fetch(
+    client: &Client,
+    cache: &Cache,
+    day: u8,
+    now: SystemTime,
+) -> Result<Fetched, ClientError> {
+    if !(1..=25).contains(&day) {
+        return Err(ClientError::InvalidDay(day));
+    }
+
+    let path = cache.path(day);
+
+    if path.exists() {
+        return Ok(Fetched::Cached(path));
+    }
+
+    check_unlocked(day, now)?;
+
+    let input = client.input(day)?;
+    Ok(Fetched::Downloaded(cache.put(day, &input)?))
+}
+
+fn check_unlocked(day: u8, now: SystemTime) -> Result<(), ClientError> {
+    match unlocks(day)
+        .duration_since(now)
+        .ok()
+        .filter(|wait| !wait.is_zero())
+    {
+        Some(unlocks_in) => Err(ClientError::Locked { day, unlocks_in }),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Not judged, the last answer was given too recently.
+    TooSoon,
+    /// Not judged, the part is solved already or not open yet (part 2
+    /// before part 1 is solved).
+    WrongLevel,
+}
+
+impl Verdict {
+    pub fn name(self) -> &'static str {
+        match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::TooSoon => "too-soon",
+            Verdict::WrongLevel => "wrong-level",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Verdict::Right,
+            Verdict::Wrong,
+            Verdict::TooHigh,
+            Verdict::TooLow,
+            Verdict::TooSoon,
+            Verdict::WrongLevel,
+        ]
+        .into_iter()
+        .find(|verdict| verdict.name() == name)
+    }
+
+    /// Whether the answer was judged wrong.
+    pub fn is_wrong(self) -> bool {
+        matches!(self, Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow)
+    }
+}
+
+/// What the website made of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reply {
+    pub verdict: Verdict,
+    /// How long to wait before giving another answer, if it said so.
+    pub wait: Option<Duration>,
+}
+
+impl Reply {
+    /// Reads the reply from the page returned for an answer, `None` if it
+    /// says something else.
+    pub fn parse(page: &str) -> Option<Self> {
+        let text = article(page);
+        let wait = wait(&text);
+
+        let verdict = if text.contains("That's the right answer") {
+            Verdict::Right
+        } else if text.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else if text.contains("You gave an answer too recently") {
+            Verdict::TooSoon
+        } else if text.contains("That's not the right answer") {
+            if text.contains("your answer is too high") {
+                Verdict::TooHigh
+            } else if text.contains("your answer is too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            }
+        } else {
+            return None;
+        };
+
+        Some(Reply { verdict, wait })
+    }
+}
+
+/// The text of the page's `<article>`, or of all of it without one, with the
+/// tags taken out and the whitespace collapsed.
+fn article(page: &str) -> String {
+    let start = page.find("<article").unwrap_or(0);
+    let end = page[start..]
+        .find("</article>")
+        .map_or(page.len(), |end| start + end);
+
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in page[start..end].chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The wait in "You have 1m 5s left to wait" or "please wait 5 minutes
+/// before trying again".
+fn wait(text: &str) -> Option<Duration> {
+    if let Some((before, _)) = text.split_once(" left to wait") {
+        let (_, left) = before.rsplit_once("You have ")?;
+
+        return left.split(' ').try_fold(Duration::ZERO, |wait, amount| {
+            let unit = match amount.chars().last()? {
+                'h' => 60 * 60,
+                'm' => 60,
+                's' => 1,
+                _ => return None,
+            };
+            let count = amount[..amount.len() - 1].parse::<u64>().ok()?;
+
+            Some(wait + Duration::from_secs(count * unit))
+        });
+    }
+
+    let (_, after) = text.split_once("wait ")?;
+    let mut words = after.split(' ');
+    let count = match words.next()? {
+        "one" => 1,
+        count => count.parse::<u64>().ok()?,
+    };
+
+    match words.next()?.trim_end_matches(|c: char| !c.is_alphabetic()) {
+        "minute" | "minutes" => Some(Duration::from_secs(count * 60)),
+        "second" | "seconds" => Some(Duration::from_secs(count)),
+        _ => None,
+    }
+}
+
+/// An answer given earlier, one per line of the history file:
+/// the time in seconds since the epoch, day, part, verdict, seconds to wait
+/// after it and the answer, separated by tabs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub at: SystemTime,
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub reply: Reply,
+}
+
+/// Why an answer isn't worth giving, known from the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Known {
+    /// The part is solved, with this answer.
+    Solved(String),
+    /// The same answer was judged wrong.
+    Wrong(Verdict),
+    /// A number at least as high as this answer, which was too high.
+    TooHigh(String),
+    /// A number at most as low as this answer, which was too low.
+    TooLow(String),
+}
+
+impl Display for Known {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Known::Solved(answer) => write!(f, "solved already, the answer is {answer}"),
+            Known::Wrong(verdict) => write!(f, "given before, it was {}", verdict.name()),
+            Known::TooHigh(answer) => write!(f, "{answer} was too high already"),
+            Known::TooLow(answer) => write!(f, "{answer} was too low already"),
+        }
+    }
+}
+
+/// The answers given so far, kept in a file to never give a wrong one twice.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    path: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl History {
+    /// Reads the history from `path`, empty if there's no such file yet.
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let submissions = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                Submission::parse(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: line {}: can't read {line:?}", path.display(), idx + 1),
+                    )
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self { path, submissions })
+    }
+
+    pub fn submissions(&self) -> &[Submission] {
+        &self.submissions
+    }
+
+    /// What's known about the `answer` to a `part` of the `day` already,
+    /// if anything.
+    pub fn known(&self, day: u8, part: u8, answer: &str) -> Option<Known> {
+        let given = self
+            .submissions
+            .iter()
+            .filter(|submission| submission.day == day && submission.part == part);
+        let number = answer.parse::<i64>().ok();
+        let mut known = None;
+
+        for submission in given {
+            let earlier = submission.answer.parse::<i64>().ok();
+
+            match submission.reply.verdict {
+                Verdict::Right => return Some(Known::Solved(submission.answer.clone())),
+                verdict if verdict.is_wrong() && submission.answer == answer => {
+                    known = Some(Known::Wrong(verdict))
+                }
+                Verdict::TooHigh if number >= earlier && earlier.is_some() => {
+                    known = known.or(Some(Known::TooHigh(submission.answer.clone())))
+                }
+                Verdict::TooLow if number <= earlier && number.is_some() => {
+                    known = known.or(Some(Known::TooLow(submission.answer.clone())))
+                }
+                _ => {}
+            }
+        }
+
+        known
+    }
+
+    /// Until when no answer should be given, going by the waits asked for.
+    pub fn wait_until(&self) -> Option<SystemTime> {
+        self.submissions
+            .iter()
+            .filter_map(|submission| Some(submission.at + submission.reply.wait?))
+            .max()
+    }
+
+    /// Adds the `submission`, appending it to the file.
+    pub fn record(&mut self, submission: Submission) -> io::Result<()> {
+        use std::io::Write;
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{submission}")?;
+
+        self.submissions.push(submission);
+        Ok(())
+    }
+}
+
+impl Submission {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, '\t');
+        let at = UNIX_EPOCH + Duration::from_secs(fields.next()?.parse().ok()?);
+        let day = fields.next()?.parse().ok()?;
+        let part = fields.next()?.parse().ok()?;
+        let verdict = Verdict::from_name(fields.next()?)?;
+        let wait = match fields.next()? {
+            "-" => None,
+            seconds => Some(Duration::from_secs(seconds.parse().ok()?)),
+        };
+        let answer = fields.next()?.to_string();
+
+        Some(Submission {
+            at,
+            day,
+            part,
+            answer,
+            reply: Reply { verdict, wait },
+        })
+    }
+}
+
+impl Display for Submission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = self.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let wait = self
+            .reply
+            .wait
+            .map_or("-".to_string(), |wait| wait.as_secs().to_string());
+
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{wait}\t{}",
+            at.as_secs(),
+            self.day,
+            self.part,
+            self.reply.verdict.name(),
+            self.answer
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Submitted {
+    /// Not sent, the history tells already.
+    Known(Known),
+    /// Not sent, a previous reply asked to wait this long yet.
+    Waiting(Duration),
+    Replied(Reply),
+}
+
+/// Gives the `answer`, trimmed, to a `part` of the `day` at `now`, unless the
+/// history knows it's wrong or asks to wait, recording the reply.
+pub fn submit(
+    client: &Client,
+    history: &mut History,
+    day: u8,
+    part: u8,
+    answer: &str,
+    now: SystemTime,
+) -> Result<Submitted, ClientError> {
+    if !(1..=25).contains(&day) {
+        return Err(ClientError::InvalidDay(day));
+    }
+
+    if !(1..=2).contains(&part) {
+        return Err(ClientError::InvalidPart(part));
+    }
+
+    let answer = answer.trim();
+
+    if answer.is_empty() || answer.contains(char::is_control) {
+        return Err(ClientError::InvalidAnswer(answer.to_string()));
+    }
+
+    check_unlocked(day, now)?;
+
+    if let Some(known) = history.known(day, part, answer) {
+        return Ok(Submitted::Known(known));
+    }
+
+    if let Some(wait) = history
+        .wait_until()
+        .and_then(|until| until.duration_since(now).ok())
+        .filter(|wait| !wait.is_zero())
+    {
+        return Ok(Submitted::Waiting(wait));
+    }
+
+    let reply = client.submit(day, part, answer)?;
+    history.record(Submission {
+        at: now,
+        day,
+        part,
+        answer: answer.to_string(),
+        reply,
+    })?;
+
+    Ok(Submitted::Replied(reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Server, TempDir};
+
+    /// A cache in a not yet existing directory, gone with the `TempDir`.
+    fn cache(name: &str) -> (TempDir, Cache) {
+        let dir = TempDir::new(name);
+        let cache = Cache::new(dir.path().join("inputs"));
+
+        (dir, cache)
+    }
+
+    fn client(server: &Server, cache: &Cache, session: Option<&str>) -> Client {
+        let config = Config {
+            session: session.map(str::to_string),
+            base_url: server.url.clone(),
+            interval: Duration::ZERO,
+        };
+
+        Client::new(
+            config,
+            Throttle::new(cache.dir().join(".last-request"), Duration::ZERO),
+        )
+    }
+
+    fn december(day: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + (day - 1) * 24 * 60 * 60)
+    }
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse(
+            "# mine\nsession = \"abc#def\"\nbase_url = http://localhost:8080/\ninterval = 2\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                session: Some("abc#def".to_string()),
+                base_url: "http://localhost:8080".to_string(),
+                interval: Duration::from_secs(2),
+            }
+        );
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+
+        let env = |key: &str| (key == "AOC_SESSION").then(|| "xyz".to_string());
+        assert_eq!(config.with_env(env).session.as_deref(), Some("xyz"));
+
+        assert_eq!(
+            Config::parse("session = 1\ntoken = 2"),
+            Err(ConfigError::UnknownKey {
+                line: 2,
+                key: "token".to_string()
+            })
+        );
+        assert_eq!(
+            Config::parse("interval = soon"),
+            Err(ConfigError::InvalidInterval {
+                line: 1,
+                value: "soon".to_string()
+            })
+        );
+        assert!(Config::parse("session").is_err());
+    }
+
+    #[test]
+    fn test_throttle() {
+        let (_dir, cache) = cache("throttle");
+        let throttle = Throttle::new(cache.dir().join(".last-request"), Duration::from_secs(5));
+        let now = december(3);
+
+        assert_eq!(throttle.delay(None, now), Duration::ZERO);
+        assert_eq!(
+            throttle.delay(Some(now + Duration::from_secs(3)), now),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            throttle.delay(Some(now - Duration::from_secs(60)), now),
+            Duration::ZERO
+        );
+
+        // the longer wait wins
+        assert_eq!(throttle.next(), None);
+        throttle.back_off(Duration::from_secs(60), now).unwrap();
+        assert_eq!(throttle.next(), Some(now + Duration::from_secs(60)));
+        throttle.back_off(Duration::from_secs(30), now).unwrap();
+        assert_eq!(throttle.next(), Some(now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_fetch() {
+        let server = Server::start(|request| match request.path.as_str() {
+            "/2024/day/1/input" => (200, vec![], "3   4\n4   3\n".to_string()),
+            _ => (404, vec![], "404 Not Found\n".to_string()),
+        });
+        let (_dir, cache) = cache("fetch");
+        let client = client(&server, &cache, Some("secret"));
+
+        let fetched = fetch(&client, &cache, 1, december(2)).unwrap();
+        assert_eq!(fetched, Fetched::Downloaded(cache.path(1)));
+        assert_eq!(
+            std::fs::read_to_string(fetched.path()).unwrap(),
+            "3   4\n4   3\n"
+        );
+
+        // cached from now on
+        assert_eq!(
+            fetch(&client, &cache, 1, december(2)).unwrap(),
+            Fetched::Cached(cache.path(1))
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].header("cookie"), Some("session=secret"));
+        assert!(requests[0]
+            .header("user-agent")
+            .is_some_and(|agent| agent.starts_with("aoc2024/")));
+        assert!(cache.dir().join(".last-request").exists());
+
+        // neither asked for before it unlocks nor cached when it fails
+        assert!(matches!(
+            fetch(
+                &client,
+                &cache,
+                2,
+                december(2) - Duration::from_secs(90 * 60)
+            ),
+            Err(ClientError::Locked { day: 2, .. })
+        ));
+        let err = fetch(&client, &cache, 2, december(2)).unwrap_err();
+        assert_eq!(err.to_string(), "the server answered 404: 404 Not Found");
+        assert!(!cache.path(2).exists());
+        assert_eq!(server.requests().len(), 2);
+
+        assert!(matches!(
+            fetch(&client, &cache, 26, december(30)),
+            Err(ClientError::InvalidDay(26))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let server =
+            Server::start(|_| (429, vec![("Retry-After", "30".to_string())], String::new()));
+        let (_dir, cache) = cache("fetch-errors");
+
+        let err = fetch(&client(&server, &cache, None), &cache, 1, december(2)).unwrap_err();
+        assert!(matches!(err, ClientError::NoSession));
+        assert!(server.requests().is_empty());
+
+        let err = fetch(
+            &client(&server, &cache, Some("secret")),
+            &cache,
+            1,
+            december(2),
+        );
+        assert!(matches!(
+            err,
+            Err(ClientError::RateLimited {
+                retry_after: Some(30)
+            })
+        ));
+        assert!(!cache.path(1).exists());
+
+        // the next run waits as asked
+        let throttle = Throttle::new(cache.dir().join(".last-request"), Duration::ZERO);
+        let wait = throttle.delay(throttle.next(), SystemTime::now());
+        assert!(wait > Duration::from_secs(25), "{wait:?}");
+    }
+
+    fn page(article: &str) -> String {
+        format!("<html><body><main>\n<article><p>{article}</p></article>\n</main></body></html>")
+    }
+
+    #[test]
+    fn test_reply() {
+        let reply = |article: &str| Reply::parse(&page(article));
+
+        assert_eq!(
+            reply("That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to finding the Chief Historian. <a href=\"/2024/day/8#part2\">[Continue to Part Two]</a>"),
+            Some(Reply { verdict: Verdict::Right, wait: None })
+        );
+        assert_eq!(
+            reply("That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; there are also some general tips on the <a href=\"/2024/about\">about page</a>, or you can ask for hints on the <a href=\"https://www.reddit.com/r/adventofcode/\" target=\"_blank\">subreddit</a>.  Please wait one minute before trying again. <a href=\"/2024/day/8\">[Return to Day 8]</a>"),
+            Some(Reply { verdict: Verdict::TooHigh, wait: Some(Duration::from_secs(60)) })
+        );
+        assert_eq!(
+            reply("That's not the right answer; your answer is too low. Please wait 5 minutes before trying again."),
+            Some(Reply { verdict: Verdict::TooLow, wait: Some(Duration::from_secs(300)) })
+        );
+        assert_eq!(
+            reply("That's not the right answer.  If you're stuck, make sure you're using the full input data. Please wait one minute before trying again."),
+            Some(Reply { verdict: Verdict::Wrong, wait: Some(Duration::from_secs(60)) })
+        );
+        assert_eq!(
+            reply("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2024/day/8\">[Return to Day 8]</a>"),
+            Some(Reply { verdict: Verdict::TooSoon, wait: Some(Duration::from_secs(65)) })
+        );
+        assert_eq!(
+            reply("You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2024/day/8\">[Return to Day 8]</a>"),
+            Some(Reply { verdict: Verdict::WrongLevel, wait: None })
+        );
+        assert_eq!(reply("Something else entirely."), None);
+        assert_eq!(
+            article(&page("Something\n  <em>else</em>.")),
+            "Something else."
+        );
+    }
+
+    fn submission(part: u8, answer: &str, verdict: Verdict, wait: Option<u64>) -> Submission {
+        Submission {
+            at: december(8),
+            day: 8,
+            part,
+            answer: answer.to_string(),
+            reply: Reply {
+                verdict,
+                wait: wait.map(Duration::from_secs),
+            },
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let (_dir, cache) = cache("history");
+        let path = cache.dir().join("submissions");
+        let mut history = History::load(path.clone()).unwrap();
+
+        history
+            .record(submission(1, "150", Verdict::TooHigh, Some(60)))
+            .unwrap();
+        history
+            .record(submission(1, "20", Verdict::TooLow, Some(60)))
+            .unwrap();
+        history
+            .record(submission(1, "abc", Verdict::Wrong, Some(300)))
+            .unwrap();
+        history
+            .record(submission(2, "7", Verdict::Right, None))
+            .unwrap();
+        history
+            .record(Submission {
+                day: 9,
+                ..submission(2, "3", Verdict::WrongLevel, None)
+            })
+            .unwrap();
+
+        let history = History::load(path).unwrap();
+        assert_eq!(history.submissions().len(), 5);
+        assert_eq!(
+            history.submissions()[0],
+            submission(1, "150", Verdict::TooHigh, Some(60))
+        );
+
+        assert_eq!(
+            history.known(8, 1, "150"),
+            Some(Known::Wrong(Verdict::TooHigh))
+        );
+        assert_eq!(history.known(1, 1, "151"), None);
+        assert_eq!(
+            history.known(8, 1, "151"),
+            Some(Known::TooHigh("150".to_string()))
+        );
+        assert_eq!(
+            history.known(8, 1, "20"),
+            Some(Known::Wrong(Verdict::TooLow))
+        );
+        assert_eq!(
+            history.known(8, 1, "-3"),
+            Some(Known::TooLow("20".to_string()))
+        );
+        assert_eq!(
+            history.known(8, 1, "abc"),
+            Some(Known::Wrong(Verdict::Wrong))
+        );
+        assert_eq!(history.known(8, 1, "abd"), None);
+        assert_eq!(history.known(8, 1, "75"), None);
+        assert_eq!(
+            history.known(8, 2, "8"),
+            Some(Known::Solved("7".to_string()))
+        );
+        assert_eq!(history.known(9, 2, "3"), None);
+        assert_eq!(
+            history.known(8, 1, "151").unwrap().to_string(),
+            "150 was too high already"
+        );
+
+        assert_eq!(
+            history.wait_until(),
+            Some(december(8) + Duration::from_secs(300))
+        );
+
+        std::fs::write(cache.dir().join("broken"), "1\t8\t1\tmaybe\t-\t42\n").unwrap();
+        assert!(History::load(cache.dir().join("broken")).is_err());
+    }
+
+    #[test]
+    fn test_submit() {
+        let server = Server::start(|request| {
+            let article = match request.body.as_str() {
+                "level=1&answer=42" => "That's the right answer!",
+                _ => "That's not the right answer; your answer is too low. Please wait one minute before trying again.",
+            };
+
+            (200, vec![], page(article))
+        });
+        let (_dir, cache) = cache("submit");
+        let client = client(&server, &cache, Some("secret"));
+        let mut history = History::load(cache.dir().join("submissions")).unwrap();
+        let now = december(9);
+
+        assert_eq!(
+            submit(&client, &mut history, 8, 1, "41", now).unwrap(),
+            Submitted::Replied(Reply {
+                verdict: Verdict::TooLow,
+                wait: Some(Duration::from_secs(60))
+            })
+        );
+
+        // not sent again, nor before the minute is up
+        assert_eq!(
+            submit(&client, &mut history, 8, 1, "40", now).unwrap(),
+            Submitted::Known(Known::TooLow("41".to_string()))
+        );
+        assert_eq!(
+            submit(
+                &client,
+                &mut history,
+                8,
+                1,
+                "42",
+                now + Duration::from_secs(20)
+            )
+            .unwrap(),
+            Submitted::Waiting(Duration::from_secs(40))
+        );
+        assert_eq!(server.requests().len(), 1);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(
+            submit(&client, &mut history, 8, 1, "42", later).unwrap(),
+            Submitted::Replied(Reply {
+                verdict: Verdict::Right,
+                wait: None
+            })
+        );
+        assert_eq!(
+            submit(&client, &mut history, 8, 1, "42", later).unwrap(),
+            Submitted::Known(Known::Solved("42".to_string()))
+        );
+        assert_eq!(
+            submit(&client, &mut history, 8, 1, " 43\n", later).unwrap(),
+            Submitted::Known(Known::Solved("42".to_string()))
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].path, "/2024/day/8/answer");
+        assert_eq!(requests[1].header("cookie"), Some("session=secret"));
+
+        assert!(matches!(
+            submit(&client, &mut history, 8, 3, "1", later),
+            Err(ClientError::InvalidPart(3))
+        ));
+        for answer in ["", " \n", "4\t2", "4\n2"] {
+            assert!(matches!(
+                submit(&client, &mut history, 8, 2, answer, later),
+                Err(ClientError::InvalidAnswer(_))
+            ));
+        }
+        assert!(matches!(
+            submit(&client, &mut history, 10, 1, "1", later),
+            Err(ClientError::Locked { day: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn test_submit_wrong_level() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let solved = AtomicBool::new(false);
+        let server = Server::start(move |request| {
+            let article = match request.body.as_str() {
+                "level=1&answer=42" => {
+                    solved.store(true, Ordering::Relaxed);
+                    "That's the right answer!"
+                }
+                _ if !solved.load(Ordering::Relaxed) => {
+                    "You don't seem to be solving the right level.  Did you already complete it?"
+                }
+                _ => "That's the right answer!",
+            };
+
+            (200, vec![], page(article))
+        });
+        let (_dir, cache) = cache("wrong-level");
+        let client = client(&server, &cache, Some("secret"));
+        let mut history = History::load(cache.dir().join("submissions")).unwrap();
+        let now = december(9);
+
+        // part 2 before part 1 isn't judged, so it can be given again later
+        assert_eq!(
+            submit(&client, &mut history, 8, 2, "7", now).unwrap(),
+            Submitted::Replied(Reply {
+                verdict: Verdict::WrongLevel,
+                wait: None
+            })
+        );
+        assert_eq!(history.known(8, 2, "7"), None);
+
+        submit(&client, &mut history, 8, 1, "42", now).unwrap();
+        assert_eq!(
+            submit(&client, &mut history, 8, 2, "7", now).unwrap(),
+            Submitted::Replied(Reply {
+                verdict: Verdict::Right,
+                wait: None
+            })
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -4,7 +4,7 @@ fn main() {
     println!("Second part: {}", second_part(input));
 }
 
-fn build_lists(input: &str) -> (Vec<u64>, Vec<u64>) {
+pub fn build_lists(input: &str) -> (Vec<u64>, Vec<u64>) {
     input
         .lines()
         .flat_map(|line| {
@@ -25,7 +25,7 @@ fn build_lists(input: &str) -> (Vec<u64>, Vec<u64>) {
         .unzip()
 }
 
-fn first_part(input: &str) -> u64 {
+pub fn first_part(input: &str) -> u64 {
     let (mut left, mut right) = build_lists(input);
 
     left.sort();
@@ -37,7 +37,7 @@ fn first_part(input: &str) -> u64 {
         .sum()
 }
 
-fn second_part(input: &str) -> u64 {
+pub fn second_part(input: &str) -> u64 {
     let (left, right) = build_lists(input);
     left.iter()
         .map(|left_num| {
@@ -52,7 +52,7 @@ fn second_part(input: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{first_part, second_part};
+    use super::{first_part, second_part};
 
     #[test]
     fn test_first_part() {
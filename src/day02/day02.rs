@@ -4,7 +4,7 @@ fn main() {
     println!("Second part: {}", second_part(input));
 }
 
-fn build_reports(input: &str) -> Vec<Vec<u64>> {
+pub fn build_reports(input: &str) -> Vec<Vec<u64>> {
     input
         .lines()
         .map(|line| {
@@ -33,7 +33,7 @@ impl SafeReport for Vec<u64> {
     }
 }
 
-fn first_part(input: &str) -> u64 {
+pub fn first_part(input: &str) -> u64 {
     let reports = build_reports(input);
 
     reports.iter().fold(0, |mut acc, report| {
@@ -45,7 +45,7 @@ fn first_part(input: &str) -> u64 {
     })
 }
 
-fn second_part(input: &str) -> u64 {
+pub fn second_part(input: &str) -> u64 {
     let reports = build_reports(input);
     reports.iter().fold(0, |mut acc, report| {
         if report.is_safe() {
@@ -54,7 +54,7 @@ fn second_part(input: &str) -> u64 {
 
         let report_copy = report.clone();
 
-        for (idx, _) in report.into_iter().enumerate() {
+        for (idx, _) in report.iter().enumerate() {
             let mut report_copy = report_copy.clone();
             report_copy.remove(idx);
 
@@ -69,7 +69,7 @@ fn second_part(input: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{first_part, second_part};
+    use super::{first_part, second_part};
 
     #[test]
     fn test_first_part() {
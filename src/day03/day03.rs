@@ -1,16 +1,74 @@
-use std::iter::Peekable;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    fs::File,
+    io::{self, Read},
+    iter::{Copied, Peekable},
+    ops::Range,
+    rc::Rc,
+    str::Chars,
+};
 
 fn main() {
     let input = include_str!("input");
-    println!("First part: {}", first_part(input));
-    println!("Second part: {}", second_part(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if args.is_empty() {
+        println!("First part: {}", first_part(input));
+        println!("Second part: {}", second_part(input));
+        return;
+    }
+
+    let options = match Options::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    if options.report {
+        let text = match &options.file {
+            Some(path) => match std::fs::read(path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(err) => {
+                    eprintln!("{path}: {err}");
+                    std::process::exit(1);
+                }
+            },
+            None => input.to_string(),
+        };
+
+        print_report(&report(&text, options.numbers));
+        return;
+    }
+
+    let (first, second) = match &options.file {
+        Some(path) => match File::open(path).and_then(|file| scan(file, options.numbers)) {
+            Ok(sums) => sums,
+            Err(err) => {
+                eprintln!("{path}: {err}");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let tokens = tokenize_with(input, options.numbers);
+            let second: i128 = Parser::new(&tokens).map(i128::from).sum();
+            let first: i128 = parse(tokens).into_iter().map(i128::from).sum();
+            (first, second)
+        }
+    };
+
+    println!("First part: {first}");
+    println!("Second part: {second}");
 }
 
-#[derive(Debug, PartialEq)]
-enum Token {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
     Start,
     LeftParen,
-    Number(u32),
+    Number(i64),
     RightParen,
     Comma,
     Invalid,
@@ -18,36 +76,420 @@ enum Token {
     Disabled,
 }
 
-struct Tokenizer<'c> {
-    chars: Peekable<std::str::Chars<'c>>,
+/// Keywords recognised by default, mapped to the token they produce.
+const KEYWORDS: [(&str, Token); 3] = [
+    ("mul", Token::Start),
+    ("do()", Token::Enabled),
+    ("don't()", Token::Disabled),
+];
+
+#[derive(Debug, Clone, Default)]
+struct KeywordNode {
+    children: HashMap<char, usize>,
+    token: Option<Token>,
+}
+
+/// Trie over keyword spellings, matched longest-first so keywords sharing a
+/// prefix (`do()` and `don't()`) don't shadow each other.
+#[derive(Debug, Clone)]
+struct KeywordTable {
+    nodes: Vec<KeywordNode>,
+}
+
+impl KeywordTable {
+    fn new(keywords: &[(&str, Token)]) -> Self {
+        let mut nodes = vec![KeywordNode::default()];
+
+        for (keyword, token) in keywords.iter().filter(|(keyword, _)| !keyword.is_empty()) {
+            let mut node = 0;
+
+            for c in keyword.chars() {
+                node = match nodes[node].children.get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(KeywordNode::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].children.insert(c, next);
+                        next
+                    }
+                };
+            }
+
+            nodes[node].token = Some(*token);
+        }
+
+        Self { nodes }
+    }
+
+    /// Returns the token of the longest keyword at the start of the input, looked at
+    /// through `peek_nth`, and its length in chars.
+    fn longest_match(
+        &self,
+        mut peek_nth: impl FnMut(usize) -> Option<char>,
+    ) -> Option<(Token, usize)> {
+        let mut node = 0;
+        let mut longest = None;
+
+        for (idx, c) in (0..).map_while(|idx| Some((idx, peek_nth(idx)?))) {
+            let Some(&next) = self.nodes[node].children.get(&c) else {
+                break;
+            };
+
+            node = next;
+
+            if let Some(token) = self.nodes[node].token {
+                longest = Some((token, idx + 1));
+            }
+        }
+
+        longest
+    }
+}
+
+impl Default for KeywordTable {
+    fn default() -> Self {
+        Self::new(&KEYWORDS)
+    }
+}
+
+/// What to do with a run of digits longer than `NumberConfig::max_digits`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Overlong {
+    /// The whole run becomes a single `Token::Invalid`.
+    Invalid,
+    /// The run is split into numbers of at most `max_digits` digits each.
+    Split,
+}
+
+/// Rules for recognising numeric literals in the tokenizer.
+///
+/// The default matches the puzzle: unsigned decimal numbers with up to
+/// three digits, longer runs being split.
+#[derive(Debug, Clone, Copy)]
+struct NumberConfig {
+    /// Maximum digit count of a literal (without sign or hex prefix), `None` for no limit.
+    max_digits: Option<usize>,
+    allow_negative: bool,
+    allow_hex: bool,
+    overlong: Overlong,
+}
+
+impl Default for NumberConfig {
+    fn default() -> Self {
+        Self {
+            max_digits: Some(3),
+            allow_negative: false,
+            allow_hex: false,
+            overlong: Overlong::Split,
+        }
+    }
+}
+
+/// Command line options for runs other than the plain puzzle answers.
+struct Options {
+    numbers: NumberConfig,
+    /// Read this file instead of the bundled input, streaming it unless reporting.
+    file: Option<String>,
+    /// List every `mul` candidate instead of only the sums.
+    report: bool,
+}
+
+impl Options {
+    /// Parses `--max-digits <n|none>`, `--negative`, `--hex`, `--overlong <invalid|split>`
+    /// `--file <path>` and `--report`, starting from the default number rules.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            numbers: NumberConfig::default(),
+            file: None,
+            report: false,
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--max-digits" => {
+                    options.numbers.max_digits = match args.next().map(String::as_str) {
+                        Some("none") => None,
+                        Some(max) => Some(
+                            max.parse::<usize>()
+                                .ok()
+                                .filter(|max| *max > 0)
+                                .ok_or(format!("invalid digit count: {max}"))?,
+                        ),
+                        None => return Err("missing value for --max-digits".to_string()),
+                    }
+                }
+                "--negative" => options.numbers.allow_negative = true,
+                "--hex" => options.numbers.allow_hex = true,
+                "--overlong" => {
+                    options.numbers.overlong = match args.next().map(String::as_str) {
+                        Some("invalid") => Overlong::Invalid,
+                        Some("split") => Overlong::Split,
+                        Some(other) => return Err(format!("invalid overlong mode: {other}")),
+                        None => return Err("missing value for --overlong".to_string()),
+                    }
+                }
+                "--file" => match args.next() {
+                    Some(path) => options.file = Some(path.clone()),
+                    None => return Err("missing value for --file".to_string()),
+                },
+                "--report" => options.report = true,
+                other => return Err(format!("unknown argument: {other}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Char source that buffers only as many chars as have been peeked at.
+struct Lookahead<I: Iterator<Item = char>> {
+    source: I,
+    buffer: VecDeque<char>,
+    /// Bytes (in UTF-8) handed out by `next` so far.
+    offset: usize,
+}
+
+impl<I: Iterator<Item = char>> Lookahead<I> {
+    fn new(source: I) -> Self {
+        Self {
+            source,
+            buffer: VecDeque::new(),
+            offset: 0,
+        }
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Option<char> {
+        while self.buffer.len() <= n {
+            self.buffer.push_back(self.source.next()?);
+        }
+
+        Some(self.buffer[n])
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.peek_nth(0)
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Lookahead<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.buffer.pop_front().or_else(|| self.source.next())?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+}
+
+/// Decodes chars from a reader through a fixed-size buffer.
+///
+/// Invalid UTF-8 becomes `char::REPLACEMENT_CHARACTER`, sequences split across
+/// reads are reassembled. A read error ends the iteration and is stored in `error`.
+struct ReadChars<R: Read> {
+    reader: R,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    error: Rc<RefCell<Option<io::Error>>>,
+}
+
+impl<R: Read> ReadChars<R> {
+    fn new(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; capacity.max(4)].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            eof: false,
+            error: Rc::default(),
+        }
+    }
+
+    fn fill(&mut self) {
+        self.buffer.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+
+        loop {
+            match self.reader.read(&mut self.buffer[self.end..]) {
+                Ok(0) => self.eof = true,
+                Ok(read) => self.end += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    *self.error.borrow_mut() = Some(err);
+                    self.eof = true;
+                }
+            }
+            break;
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let pending = &self.buffer[self.start..self.end];
+
+            if let Some(&lead) = pending.first() {
+                let width = match lead {
+                    0x00..=0x7f => {
+                        self.start += 1;
+                        return Some(lead as char);
+                    }
+                    0xc0..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    0xf0..=0xf7 => 4,
+                    _ => 1,
+                };
+
+                if pending.len() >= width || self.eof {
+                    let width = width.min(pending.len());
+
+                    return match std::str::from_utf8(&pending[..width]) {
+                        Ok(decoded) => {
+                            self.start += width;
+                            decoded.chars().next()
+                        }
+                        Err(_) => {
+                            self.start += 1;
+                            Some(char::REPLACEMENT_CHARACTER)
+                        }
+                    };
+                }
+            } else if self.eof {
+                return None;
+            }
+
+            self.fill();
+        }
+    }
+}
+
+struct Tokenizer<I: Iterator<Item = char>> {
+    chars: Lookahead<I>,
+    config: NumberConfig,
+    keywords: KeywordTable,
+}
+
+impl Tokenizer<Chars<'_>> {
+    fn new(input: &str) -> Tokenizer<Chars<'_>> {
+        Tokenizer::with_config(input, NumberConfig::default())
+    }
+
+    fn with_config(input: &str, config: NumberConfig) -> Tokenizer<Chars<'_>> {
+        Tokenizer::with_keywords(input, config, KeywordTable::default())
+    }
+
+    fn with_keywords(
+        input: &str,
+        config: NumberConfig,
+        keywords: KeywordTable,
+    ) -> Tokenizer<Chars<'_>> {
+        Tokenizer::from_chars(input.chars(), config, keywords)
+    }
 }
 
-impl Tokenizer<'_> {
-    fn new(input: &str) -> Tokenizer {
+impl<I: Iterator<Item = char>> Tokenizer<I> {
+    fn from_chars(chars: I, config: NumberConfig, keywords: KeywordTable) -> Self {
         Tokenizer {
-            chars: input.chars().peekable(),
+            chars: Lookahead::new(chars),
+            config,
+            keywords,
+        }
+    }
+
+    /// Like `next`, but also returns the byte range the token was read from.
+    fn next_spanned(&mut self) -> Option<(Range<usize>, Token)> {
+        let start = self.chars.offset;
+        let token = self.next()?;
+
+        Some((start..self.chars.offset, token))
+    }
+
+    fn starts_number(&mut self, c: char) -> bool {
+        match c {
+            '-' if self.config.allow_negative => {
+                matches!(self.chars.peek(), Some(c) if c.is_ascii_digit())
+            }
+            c => c.is_ascii_digit(),
+        }
+    }
+
+    fn has_hex_prefix(&mut self) -> bool {
+        matches!(
+            (self.chars.peek_nth(0), self.chars.peek_nth(1)),
+            (Some('x' | 'X'), Some(c)) if c.is_ascii_hexdigit()
+        )
+    }
+
+    fn number(&mut self, first: char) -> Token {
+        let negative = first == '-';
+        let mut first = if negative {
+            self.chars.next().expect("digit after sign")
+        } else {
+            first
+        };
+
+        let mut radix = 10;
+
+        if self.config.allow_hex && first == '0' && self.has_hex_prefix() {
+            self.chars.next();
+            first = self.chars.next().expect("hex digit after prefix");
+            radix = 16;
+        }
+
+        let mut value = Some(first.to_digit(radix).expect("digit") as i64);
+        let mut len = 1;
+
+        while let Some(digit) = self.chars.peek().and_then(|c| c.to_digit(radix)) {
+            if self.config.max_digits.is_some_and(|max| len >= max) {
+                match self.config.overlong {
+                    Overlong::Split => break,
+                    Overlong::Invalid => value = None,
+                }
+            }
+
+            value = value
+                .and_then(|value| value.checked_mul(radix as i64))
+                .and_then(|value| value.checked_add(digit as i64));
+            len += 1;
+            self.chars.next();
+        }
+
+        match value {
+            Some(value) if negative => Token::Number(-value),
+            Some(value) => Token::Number(value),
+            None => Token::Invalid,
         }
     }
 }
 
-struct Parser<'t> {
-    tokens: Peekable<std::slice::Iter<'t, Token>>,
+struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
     enabled: bool,
 }
 
-impl<'t> Parser<'t> {
-    fn new(tokens: &Vec<Token>) -> Parser {
+impl Parser<Copied<std::slice::Iter<'_, Token>>> {
+    fn new(tokens: &[Token]) -> Parser<Copied<std::slice::Iter<'_, Token>>> {
+        Parser::from_tokens(tokens.iter().copied())
+    }
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    fn from_tokens(tokens: I) -> Self {
         Parser {
-            tokens: tokens.iter().peekable(),
+            tokens: tokens.peekable(),
             enabled: true,
         }
     }
-}
-
-impl Iterator for Parser<'_> {
-    type Item = Multiplication;
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Returns the next well-formed multiplication, enabled or not, together with
+    /// whether the last `do()`/`don't()` before it enabled it.
+    fn next_with_state(&mut self) -> Option<(Multiplication, bool)> {
         while let Some(token) = self.tokens.next() {
             match token {
                 Token::Enabled => {
@@ -58,45 +500,44 @@ impl Iterator for Parser<'_> {
                     self.enabled = false;
                     continue;
                 }
-                Token::Start if self.enabled => {
-                    let mut num1 = None;
-                    let mut num2 = None;
-
-                    if let Some(_token @ Token::LeftParen) = self.tokens.peek() {
+                Token::Start => {
+                    if let Some(Token::LeftParen) = self.tokens.peek() {
                         self.tokens.next();
                     } else {
                         continue;
                     };
 
-                    if let Some(&Token::Number(num)) = self.tokens.peek() {
-                        num1 = Some(*num);
+                    let num1 = if let Some(&Token::Number(num)) = self.tokens.peek() {
                         self.tokens.next();
+                        num
                     } else {
                         continue;
-                    }
+                    };
 
-                    if let Some(_token @ Token::Comma) = self.tokens.peek() {
+                    if let Some(Token::Comma) = self.tokens.peek() {
                         self.tokens.next();
                     } else {
                         continue;
                     }
 
-                    if let Some(_token @ Token::Number(num)) = self.tokens.peek() {
-                        num2 = Some(*num);
+                    let num2 = if let Some(&Token::Number(num)) = self.tokens.peek() {
                         self.tokens.next();
+                        num
                     } else {
                         continue;
-                    }
+                    };
 
-                    if let Some(_token @ Token::RightParen) = self.tokens.peek() {
+                    if let Some(Token::RightParen) = self.tokens.peek() {
                         self.tokens.next();
                     } else {
                         continue;
                     }
 
-                    if let (Some(num1), Some(num2)) = (num1, num2) {
-                        return Some(Multiplication(num1, num2));
-                    }
+                    let Some(multiplication) = Multiplication::new(num1, num2) else {
+                        continue;
+                    };
+
+                    return Some((multiplication, self.enabled));
                 }
                 _ => continue,
             }
@@ -105,103 +546,298 @@ impl Iterator for Parser<'_> {
     }
 }
 
-impl Iterator for Tokenizer<'_> {
+impl<I: Iterator<Item = Token>> Iterator for Parser<I> {
+    type Item = Multiplication;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_with_state()? {
+                (multiplication, true) => return Some(multiplication),
+                (_, false) => continue,
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Tokenizer<I> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let chars = &mut self.chars;
+
+        if let Some((token, len)) = self.keywords.longest_match(|idx| chars.peek_nth(idx)) {
+            self.chars.nth(len - 1);
+            return Some(token);
+        }
+
         let c = self.chars.next()?;
 
         match c {
-            c if c == 'm' => match self.chars.peek() {
-                Some('u') => {
-                    self.chars.next();
-                    match self.chars.peek() {
-                        Some('l') => {
-                            self.chars.next();
-                            Some(Token::Start)
-                        }
-                        _ => Some(Token::Invalid),
-                    }
-                }
-                _ => Some(Token::Invalid),
-            },
-            c if c == 'd' => match self.chars.peek() {
-                Some('o') => {
-                    self.chars.next();
-
-                    match self.chars.peek() {
-                        Some('n') => {
-                            self.chars.next();
-                            let first = self.chars.next();
-                            let second = self.chars.next();
-                            let third = self.chars.next();
-                            let fourth = self.chars.next();
-
-                            match (first, second, third, fourth) {
-                                (Some('\''), Some('t'), Some('('), Some(')')) => {
-                                    Some(Token::Disabled)
-                                }
-                                _ => return Some(Token::Invalid),
-                            }
-                        }
-                        Some('(') => {
-                            self.chars.next();
-                            match self.chars.peek() {
-                                Some(')') => {
-                                    self.chars.next();
-                                    Some(Token::Enabled)
-                                }
-                                _ => return Some(Token::Invalid),
-                            }
-                        }
-                        _ => Some(Token::Invalid),
-                    }
-                }
-                _ => Some(Token::Invalid),
-            },
             '(' => Some(Token::LeftParen),
             ')' => Some(Token::RightParen),
             ',' => Some(Token::Comma),
-            c if c.is_digit(10) => match c.to_digit(10) {
-                Some(d) => {
-                    let mut digits = vec![d];
-
-                    while digits.len() < 3 {
-                        match self.chars.peek() {
-                            Some(c) if c.is_digit(10) => {
-                                let d = c.to_digit(10).unwrap();
-                                digits.push(d);
-                                self.chars.next();
-                            }
-                            _ => break,
-                        }
-                    }
+            c if self.starts_number(c) => Some(self.number(c)),
+            _ => Some(Token::Invalid),
+        }
+    }
+}
 
-                    let number = digits.iter().fold(0, |acc, num| acc * 10 + num);
+/// Lazily scans a reader for multiplications without holding more than a
+/// fixed-size buffer of it in memory.
+struct Scanner<R: Read> {
+    parser: Parser<Tokenizer<ReadChars<R>>>,
+    error: Rc<RefCell<Option<io::Error>>>,
+}
 
-                    Some(Token::Number(number))
-                }
-                None => Some(Token::Invalid),
-            },
-            _ => Some(Token::Invalid),
+impl<R: Read> Scanner<R> {
+    const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+    fn new(reader: R, config: NumberConfig) -> Self {
+        Self::with_capacity(reader, config, Self::DEFAULT_CAPACITY)
+    }
+
+    fn with_capacity(reader: R, config: NumberConfig, capacity: usize) -> Self {
+        let chars = ReadChars::new(reader, capacity);
+        let error = Rc::clone(&chars.error);
+        let tokenizer = Tokenizer::from_chars(chars, config, KeywordTable::default());
+
+        Self {
+            parser: Parser::from_tokens(tokenizer),
+            error,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Scanner<R> {
+    /// A multiplication and whether it is enabled, or the error that ended the scan.
+    type Item = io::Result<(Multiplication, bool)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.parser.next_with_state() {
+            Some(scanned) => Some(Ok(scanned)),
+            None => self.error.borrow_mut().take().map(Err),
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-struct Multiplication(u32, u32);
+pub struct Multiplication(i64, i64);
+
+impl Multiplication {
+    /// The multiplication of `left` and `right`, `None` if their product
+    /// doesn't fit in an `i64`.
+    fn new(left: i64, right: i64) -> Option<Self> {
+        left.checked_mul(right).map(|_| Self(left, right))
+    }
+}
+
+impl From<Multiplication> for i64 {
+    fn from(multiplication: Multiplication) -> i64 {
+        multiplication.0 * multiplication.1
+    }
+}
 
-impl From<Multiplication> for u64 {
-    fn from(multiplication: Multiplication) -> u64 {
-        multiplication.0 as u64 * multiplication.1 as u64
+/// Widens the product, so that the sums can't overflow.
+impl From<Multiplication> for i128 {
+    fn from(multiplication: Multiplication) -> i128 {
+        i64::from(multiplication).into()
     }
 }
 
-fn tokenize(input: &str) -> Vec<Token> {
+pub fn tokenize(input: &str) -> Vec<Token> {
     Tokenizer::new(input).collect()
 }
 
-fn parse(tokens: Vec<Token>) -> Vec<Multiplication> {
+fn tokenize_with(input: &str, config: NumberConfig) -> Vec<Token> {
+    Tokenizer::with_config(input, config).collect()
+}
+
+fn tokenize_spanned(input: &str, config: NumberConfig) -> Vec<(Range<usize>, Token)> {
+    let mut tokenizer = Tokenizer::with_config(input, config);
+
+    std::iter::from_fn(|| tokenizer.next_spanned()).collect()
+}
+
+/// Why a `mul` candidate doesn't count.
+#[derive(Debug, Clone, PartialEq)]
+enum Rejection {
+    /// Well-formed, but switched off by the `don't()` at this offset.
+    Disabled(usize),
+    /// Whitespace where `expected` should have been.
+    Whitespace { expected: &'static str },
+    /// A number with more digits than the tokenizer accepts.
+    NumberTooLong,
+    /// Well-formed, but the product doesn't fit in an `i64`.
+    Overflow,
+    /// Something other than `expected`, `found` is `None` at the end of input.
+    Unexpected {
+        expected: &'static str,
+        found: Option<String>,
+    },
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Disabled(offset) => write!(f, "disabled by don't() at {offset}"),
+            Rejection::Whitespace { expected } => write!(f, "whitespace instead of {expected}"),
+            Rejection::NumberTooLong => write!(f, "number has too many digits"),
+            Rejection::Overflow => write!(f, "product overflows"),
+            Rejection::Unexpected {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected {expected}, found {found:?}"),
+            Rejection::Unexpected {
+                expected,
+                found: None,
+            } => write!(f, "expected {expected}, found end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Verdict {
+    Accepted(Multiplication),
+    Rejected(Rejection),
+}
+
+/// A `mul` keyword in the input and what became of it.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate<'i> {
+    /// Byte offset of the `mul`.
+    offset: usize,
+    /// The candidate up to and including the token that decided the verdict.
+    text: &'i str,
+    verdict: Verdict,
+}
+
+/// Lists every `mul` candidate in `input`, following the same rules as `Parser`.
+fn report(input: &str, config: NumberConfig) -> Vec<Candidate<'_>> {
+    let tokens = tokenize_spanned(input, config);
+    let mut disabled_at = None;
+    let mut candidates = Vec::new();
+
+    for (idx, (span, token)) in tokens.iter().enumerate() {
+        match token {
+            Token::Enabled => disabled_at = None,
+            Token::Disabled => disabled_at = Some(span.start),
+            Token::Start => {
+                let (end, verdict) = judge(input, &tokens[idx..], disabled_at, config);
+
+                candidates.push(Candidate {
+                    offset: span.start,
+                    text: &input[span.start..end],
+                    verdict,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    candidates
+}
+
+/// Judges the candidate starting with the `Token::Start` at `tokens[0]`,
+/// returning where its text ends and the verdict.
+fn judge(
+    input: &str,
+    tokens: &[(Range<usize>, Token)],
+    disabled_at: Option<usize>,
+    config: NumberConfig,
+) -> (usize, Verdict) {
+    const PATTERN: [&str; 5] = ["`(`", "a number", "`,`", "a number", "`)`"];
+
+    let mut end = tokens[0].0.end;
+    let mut numbers = Vec::new();
+
+    for (step, expected) in PATTERN.into_iter().enumerate() {
+        let Some((span, token)) = tokens.get(step + 1) else {
+            let rejection = Rejection::Unexpected {
+                expected,
+                found: None,
+            };
+            return (end, Verdict::Rejected(rejection));
+        };
+
+        match (expected, token) {
+            ("`(`", Token::LeftParen) | ("`,`", Token::Comma) | ("`)`", Token::RightParen) => {}
+            ("a number", Token::Number(number)) => numbers.push(*number),
+            _ => {
+                let text = &input[span.clone()];
+                // a number split off the end of one that hit the digit limit
+                let split_off = match &tokens[step] {
+                    (prev, Token::Number(_)) if prev.end == span.start => {
+                        let digits = input[prev.clone()].trim_start_matches('-');
+                        let digits = digits
+                            .strip_prefix("0x")
+                            .or_else(|| digits.strip_prefix("0X"))
+                            .unwrap_or(digits);
+
+                        config.max_digits == Some(digits.len()) && !text.starts_with('-')
+                    }
+                    _ => false,
+                };
+
+                let rejection = match token {
+                    Token::Number(_) if split_off => Rejection::NumberTooLong,
+                    Token::Invalid if text.chars().count() > 1 => Rejection::NumberTooLong,
+                    Token::Invalid if text.chars().all(char::is_whitespace) => {
+                        Rejection::Whitespace { expected }
+                    }
+                    _ => Rejection::Unexpected {
+                        expected,
+                        found: Some(text.to_string()),
+                    },
+                };
+
+                return (span.end, Verdict::Rejected(rejection));
+            }
+        }
+
+        end = span.end;
+    }
+
+    let verdict = match (Multiplication::new(numbers[0], numbers[1]), disabled_at) {
+        (None, _) => Verdict::Rejected(Rejection::Overflow),
+        (Some(_), Some(offset)) => Verdict::Rejected(Rejection::Disabled(offset)),
+        (Some(multiplication), None) => Verdict::Accepted(multiplication),
+    };
+
+    (end, verdict)
+}
+
+fn print_report(candidates: &[Candidate]) {
+    let mut accepted = 0;
+
+    for candidate in candidates {
+        let text = candidate.text.escape_debug().to_string();
+
+        match &candidate.verdict {
+            Verdict::Accepted(multiplication) => {
+                accepted += 1;
+                println!(
+                    "{:>10}  accepted  {text:<20}  = {}",
+                    candidate.offset,
+                    i64::from(*multiplication)
+                );
+            }
+            Verdict::Rejected(rejection) => {
+                println!(
+                    "{:>10}  rejected  {text:<20}  {rejection}",
+                    candidate.offset
+                );
+            }
+        }
+    }
+
+    println!(
+        "{} candidates, {accepted} accepted, {} rejected",
+        candidates.len(),
+        candidates.len() - accepted
+    );
+}
+
+pub fn parse(tokens: Vec<Token>) -> Vec<Multiplication> {
     const MAX_TOKENS: usize = 4;
     let mut multiplications = Vec::new();
 
@@ -233,34 +869,47 @@ fn parse(tokens: Vec<Token>) -> Vec<Multiplication> {
             })
             .collect::<Vec<_>>();
 
-        if numbers.len() == 2 {
-            multiplications.push(Multiplication(numbers[0], numbers[1]));
+        if let [left, right] = numbers[..] {
+            multiplications.extend(Multiplication::new(left, right));
         }
     }
 
     multiplications
 }
 
-fn first_part(input: &str) -> u64 {
+/// Computes both parts in a single streaming pass over `reader`.
+fn scan(reader: impl Read, config: NumberConfig) -> io::Result<(i128, i128)> {
+    Scanner::new(reader, config).try_fold((0, 0), |(first, second), scanned| {
+        let (multiplication, enabled) = scanned?;
+        let product = i128::from(multiplication);
+
+        Ok((
+            first + product,
+            if enabled { second + product } else { second },
+        ))
+    })
+}
+
+pub fn first_part(input: &str) -> i128 {
     let tokens = tokenize(input);
 
-    parse(tokens)
-        .into_iter()
-        .map(|multiplication| u64::from(multiplication))
-        .sum()
+    parse(tokens).into_iter().map(i128::from).sum()
 }
 
-fn second_part(input: &str) -> u64 {
+pub fn second_part(input: &str) -> i128 {
     let tokens = tokenize(input);
 
-    Parser::new(&tokens)
-        .filter_map(|multiplication| Some(u64::from(multiplication)))
-        .sum()
+    Parser::new(&tokens).map(i128::from).sum()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{first_part, second_part, tokenize, Multiplication, Parser, Token};
+    use super::{
+        first_part, parse, report, scan, second_part, tokenize, tokenize_with, Candidate,
+        KeywordTable, Multiplication, NumberConfig, Options, Overlong, Parser, Rejection, Scanner,
+        Token, Tokenizer, Verdict,
+    };
+    use std::io::{self, Read};
 
     #[test]
     fn test_tokenizer() {
@@ -368,7 +1017,13 @@ mod tests {
         let test_cases = [
             (
                 "do(don't())",
-                vec![Token::Invalid, Token::Disabled, Token::RightParen],
+                vec![
+                    Token::Invalid,
+                    Token::Invalid,
+                    Token::LeftParen,
+                    Token::Disabled,
+                    Token::RightParen,
+                ],
             ),
             ("do()don't()", vec![Token::Enabled, Token::Disabled]),
         ];
@@ -406,7 +1061,7 @@ mod tests {
 
         let first = multiplications.first().unwrap();
 
-        assert_eq!(u64::from(*first), 123 * 456);
+        assert_eq!(i64::from(*first), 123 * 456);
     }
 
     #[test]
@@ -485,6 +1140,354 @@ mod tests {
         assert_eq!(parser.next(), Some(Multiplication(111, 333))); // Enabled
     }
 
+    #[test]
+    fn test_tokenizer_custom_keywords() {
+        let keywords = KeywordTable::new(&[
+            ("mul", Token::Start),
+            ("mul3(", Token::Disabled),
+            ("do()", Token::Enabled),
+            ("undo()", Token::Disabled),
+        ]);
+        let tokens =
+            Tokenizer::with_keywords("mul(undo()mul3(mumul", NumberConfig::default(), keywords)
+                .collect::<Vec<_>>();
+
+        let expected = vec![
+            Token::Start,
+            Token::LeftParen,
+            Token::Disabled,
+            Token::Disabled,
+            Token::Invalid,
+            Token::Invalid,
+            Token::Start,
+        ];
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_tokenizer_default_numbers() {
+        let expected = vec![
+            Token::Number(123),
+            Token::Number(4),
+            Token::Invalid,
+            Token::Number(5),
+        ];
+        assert_eq!(tokenize("1234-5"), expected);
+
+        let expected = vec![
+            Token::Number(0),
+            Token::Invalid,
+            Token::Number(1),
+            Token::Invalid,
+        ];
+        assert_eq!(tokenize("0x1f"), expected);
+    }
+
+    #[test]
+    fn test_tokenizer_number_config() {
+        let config = NumberConfig {
+            max_digits: Some(2),
+            overlong: Overlong::Invalid,
+            ..NumberConfig::default()
+        };
+
+        let expected = vec![
+            Token::Number(12),
+            Token::Comma,
+            Token::Invalid,
+            Token::Comma,
+        ];
+        assert_eq!(tokenize_with("12,12345,", config), expected);
+
+        let config = NumberConfig {
+            allow_negative: true,
+            ..NumberConfig::default()
+        };
+
+        let expected = vec![
+            Token::Number(-12),
+            Token::Comma,
+            Token::Invalid,
+            Token::Comma,
+            Token::Number(3),
+        ];
+        assert_eq!(tokenize_with("-12,-,3", config), expected);
+
+        let config = NumberConfig {
+            allow_hex: true,
+            ..NumberConfig::default()
+        };
+
+        let expected = vec![
+            Token::Number(0x1f),
+            Token::Comma,
+            Token::Number(0),
+            Token::Invalid,
+        ];
+        assert_eq!(tokenize_with("0x1F,0x", config), expected);
+
+        let config = NumberConfig {
+            max_digits: None,
+            ..NumberConfig::default()
+        };
+
+        assert_eq!(tokenize_with("123456", config), vec![Token::Number(123456)]);
+        assert_eq!(
+            tokenize_with("99999999999999999999", config),
+            vec![Token::Invalid]
+        );
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let args = ["--max-digits", "none", "--hex", "--overlong", "invalid"].map(String::from);
+        let config = Options::from_args(&args).unwrap().numbers;
+
+        assert_eq!(config.max_digits, None);
+        assert!(config.allow_hex);
+        assert!(!config.allow_negative);
+        assert_eq!(config.overlong, Overlong::Invalid);
+
+        let args = ["--max-digits", "0"].map(String::from);
+        assert!(Options::from_args(&args).is_err());
+
+        let args = ["--overlong"].map(String::from);
+        assert!(Options::from_args(&args).is_err());
+
+        let args = ["--file", "dump.bin"].map(String::from);
+        assert_eq!(
+            Options::from_args(&args).unwrap().file.as_deref(),
+            Some("dump.bin")
+        );
+    }
+
+    #[test]
+    fn test_negative_multiplication() {
+        let config = NumberConfig {
+            allow_negative: true,
+            ..NumberConfig::default()
+        };
+
+        let tokens = tokenize_with("mul(-2,4)mul(3,-3)", config);
+        let products = Parser::new(&tokens).map(i64::from).collect::<Vec<_>>();
+
+        assert_eq!(products, vec![-8, -9]);
+    }
+
+    #[test]
+    fn test_overflowing_multiplication() {
+        let config = NumberConfig {
+            max_digits: None,
+            ..NumberConfig::default()
+        };
+
+        // the product doesn't fit, so the candidate doesn't count
+        let input = "mul(9999999999,9999999999)mul(2,3)";
+        let tokens = tokenize_with(input, config);
+        let products = Parser::new(&tokens).map(i64::from).collect::<Vec<_>>();
+
+        assert_eq!(products, vec![6]);
+        assert_eq!(parse(tokens), vec![Multiplication(2, 3)]);
+        assert_eq!(scan(input.as_bytes(), config).unwrap(), (6, 6));
+        assert_eq!(
+            report(input, config)[0].verdict,
+            Verdict::Rejected(Rejection::Overflow)
+        );
+
+        // each product fits, their sum only does as an i128
+        let input = "mul(3037000499,3037000499)don't()mul(3037000499,3037000499)";
+        let product = 3037000499_i128 * 3037000499;
+
+        assert_eq!(
+            scan(input.as_bytes(), config).unwrap(),
+            (2 * product, product)
+        );
+    }
+
+    /// Reader handing out at most `chunk` bytes per read.
+    struct Trickle<'b> {
+        bytes: &'b [u8],
+        chunk: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.chunk.min(buf.len()).min(self.bytes.len());
+            buf[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes = &self.bytes[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_scanner_across_buffer_boundaries() {
+        let input = include_str!("input_test2");
+        let expected = (first_part(input), second_part(input));
+
+        for capacity in [4, 5, 7, 64] {
+            for chunk in [1, 3, 1024] {
+                let reader = Trickle {
+                    bytes: input.as_bytes(),
+                    chunk,
+                };
+                let scanned = Scanner::with_capacity(reader, NumberConfig::default(), capacity)
+                    .map(|scanned| {
+                        scanned
+                            .map(|(multiplication, enabled)| (i128::from(multiplication), enabled))
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+                    .unwrap();
+
+                let first: i128 = scanned.iter().map(|(product, _)| product).sum();
+                let second: i128 = scanned
+                    .iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(product, _)| product)
+                    .sum();
+
+                assert_eq!((first, second), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scanner_utf8_and_errors() {
+        let input = "äöü€mul(2,3)\u{1f384}mul(4,5)".as_bytes();
+        let reader = Trickle {
+            bytes: input,
+            chunk: 1,
+        };
+
+        assert_eq!(scan(reader, NumberConfig::default()).unwrap(), (26, 26));
+
+        let input = b"mul(2,\xff3)mul(1,\xe2\x82)mul(7,8)";
+        assert_eq!(scan(&input[..], NumberConfig::default()).unwrap(), (56, 56));
+
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken pipe"))
+            }
+        }
+
+        let mut scanner = Scanner::new(Failing, NumberConfig::default());
+        assert!(matches!(scanner.next(), Some(Err(_))));
+        assert!(scanner.next().is_none());
+    }
+
+    #[test]
+    fn test_report() {
+        let input = "mul(123, 456)mul(2,4)don't()mul(3,4)do()mul(1234,5)mul[3,7]mul(6,7";
+        let candidates = report(input, NumberConfig::default());
+
+        let expected = vec![
+            Candidate {
+                offset: 0,
+                text: "mul(123, ",
+                verdict: Verdict::Rejected(Rejection::Whitespace {
+                    expected: "a number",
+                }),
+            },
+            Candidate {
+                offset: 13,
+                text: "mul(2,4)",
+                verdict: Verdict::Accepted(Multiplication(2, 4)),
+            },
+            Candidate {
+                offset: 28,
+                text: "mul(3,4)",
+                verdict: Verdict::Rejected(Rejection::Disabled(21)),
+            },
+            Candidate {
+                offset: 40,
+                text: "mul(1234",
+                verdict: Verdict::Rejected(Rejection::NumberTooLong),
+            },
+            Candidate {
+                offset: 51,
+                text: "mul[",
+                verdict: Verdict::Rejected(Rejection::Unexpected {
+                    expected: "`(`",
+                    found: Some("[".to_string()),
+                }),
+            },
+            Candidate {
+                offset: 59,
+                text: "mul(6,7",
+                verdict: Verdict::Rejected(Rejection::Unexpected {
+                    expected: "`)`",
+                    found: None,
+                }),
+            },
+        ];
+
+        assert_eq!(candidates, expected);
+
+        let config = NumberConfig {
+            overlong: Overlong::Invalid,
+            ..NumberConfig::default()
+        };
+        let candidates = report("mul(1,2345)", config);
+
+        assert_eq!(
+            candidates[0].verdict,
+            Verdict::Rejected(Rejection::NumberTooLong)
+        );
+
+        let config = NumberConfig {
+            allow_negative: true,
+            ..NumberConfig::default()
+        };
+        let candidates = report("mul(1-2,3)mul(123-4,5)mul(-1234,5)", config);
+        let verdicts = candidates
+            .into_iter()
+            .map(|candidate| candidate.verdict)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            verdicts,
+            vec![
+                Verdict::Rejected(Rejection::Unexpected {
+                    expected: "`,`",
+                    found: Some("-2".to_string()),
+                }),
+                Verdict::Rejected(Rejection::Unexpected {
+                    expected: "`,`",
+                    found: Some("-4".to_string()),
+                }),
+                Verdict::Rejected(Rejection::NumberTooLong),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_matches_parts() {
+        for input in [include_str!("input_test"), include_str!("input_test2")] {
+            let candidates = report(input, NumberConfig::default());
+            let accepted: i128 = candidates
+                .iter()
+                .filter_map(|candidate| match candidate.verdict {
+                    Verdict::Accepted(multiplication) => Some(i128::from(multiplication)),
+                    _ => None,
+                })
+                .sum();
+            let disabled: i128 = candidates
+                .iter()
+                .filter_map(|candidate| match &candidate.verdict {
+                    Verdict::Rejected(Rejection::Disabled(_)) => {
+                        Some(i128::from(parse(tokenize(candidate.text))[0]))
+                    }
+                    _ => None,
+                })
+                .sum();
+
+            assert_eq!(accepted, second_part(input));
+            assert_eq!(accepted + disabled, first_part(input));
+        }
+    }
+
     #[test]
     fn test_first_part() {
         let input = include_str!("input_test");
@@ -499,3 +1502,264 @@ mod tests {
         assert_eq!(second_part(input), 48);
     }
 }
+
+/// Generated-input checks: the tokenizer never panics, the parser agrees with a
+/// plain string-scanning solution and part one never undercounts part two.
+///
+/// `fuzz` is an offline, `cargo fuzz`-style mutation loop around `fuzz_target`:
+/// `DAY03_FUZZ_ITERATIONS=1000000 cargo test --bin day03 fuzz -- --ignored`,
+/// with `DAY03_FUZZ_SEED` to replay a reported failure.
+#[cfg(test)]
+mod properties {
+    use super::{first_part, report, scan, second_part, NumberConfig, Overlong, Tokenizer};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const CASES: usize = 1_000;
+
+    const FRAGMENTS: [&str; 18] = [
+        "mul",
+        "mul(",
+        "(",
+        ")",
+        ",",
+        " ",
+        "do()",
+        "don't()",
+        "do(",
+        "don't",
+        "mu",
+        "ul(",
+        "x",
+        "-",
+        "0x",
+        "é",
+        "\u{1f384}",
+        "\n",
+    ];
+
+    /// SplitMix64, enough randomness for input generation without dependencies.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    fn configs() -> [NumberConfig; 3] {
+        [
+            NumberConfig::default(),
+            NumberConfig {
+                max_digits: None,
+                allow_negative: true,
+                allow_hex: true,
+                overlong: Overlong::Split,
+            },
+            NumberConfig {
+                max_digits: Some(1),
+                allow_negative: true,
+                allow_hex: true,
+                overlong: Overlong::Invalid,
+            },
+        ]
+    }
+
+    /// Input built from pieces of the grammar, so well-formed and almost
+    /// well-formed instructions show up often.
+    fn grammar_input(rng: &mut Rng) -> String {
+        (0..rng.below(40))
+            .map(|_| match rng.below(3) {
+                0 => (rng.next() % 10u64.pow(rng.below(5) as u32 + 1)).to_string(),
+                _ => FRAGMENTS[rng.below(FRAGMENTS.len())].to_string(),
+            })
+            .collect()
+    }
+
+    fn arbitrary_input(rng: &mut Rng) -> String {
+        (0..rng.below(64))
+            .filter_map(|_| match rng.below(2) {
+                0 => char::from_u32(rng.below(0x11_0000) as u32),
+                _ => Some(rng.below(0x80) as u8 as char),
+            })
+            .collect()
+    }
+
+    /// Straightforward string-scanning solution for both parts.
+    fn reference(input: &str) -> (i128, i128) {
+        let mut sums = (0, 0);
+        let mut enabled = true;
+
+        for (idx, _) in input.char_indices() {
+            let rest = &input[idx..];
+
+            if rest.starts_with("do()") {
+                enabled = true;
+            } else if rest.starts_with("don't()") {
+                enabled = false;
+            } else if let Some(args) = rest.strip_prefix("mul(") {
+                let Some((left, args)) = leading_number(args) else {
+                    continue;
+                };
+                let Some(args) = args.strip_prefix(',') else {
+                    continue;
+                };
+                let Some((right, args)) = leading_number(args) else {
+                    continue;
+                };
+
+                if args.starts_with(')') {
+                    let product = i128::from(left * right);
+                    sums.0 += product;
+
+                    if enabled {
+                        sums.1 += product;
+                    }
+                }
+            }
+        }
+
+        sums
+    }
+
+    fn leading_number(input: &str) -> Option<(i64, &str)> {
+        let len = input.bytes().take(3).take_while(u8::is_ascii_digit).count();
+        let number = input[..len].parse().ok()?;
+
+        Some((number, &input[len..]))
+    }
+
+    /// Checks every invariant on a single input.
+    fn fuzz_target(data: &[u8]) {
+        let input = String::from_utf8_lossy(data);
+
+        for config in configs() {
+            Tokenizer::with_config(&input, config).for_each(drop);
+            report(&input, config);
+        }
+
+        let expected = reference(&input);
+
+        assert_eq!((first_part(&input), second_part(&input)), expected);
+        assert!(expected.0 >= expected.1);
+        assert_eq!(scan(data, NumberConfig::default()).unwrap(), expected);
+    }
+
+    fn mutate(rng: &mut Rng, data: &mut Vec<u8>) {
+        const MAX_LEN: usize = 4096;
+
+        for _ in 0..=rng.below(4) {
+            let at = rng.below(data.len() + 1);
+
+            match rng.below(5) {
+                0 if at < data.len() => data[at] ^= 1 << rng.below(8),
+                1 => {
+                    let fragment = FRAGMENTS[rng.below(FRAGMENTS.len())];
+                    data.splice(at..at, fragment.bytes());
+                }
+                2 => {
+                    let end = (at + rng.below(16)).min(data.len());
+                    data.drain(at..end);
+                }
+                3 => {
+                    let end = (at + rng.below(16)).min(data.len());
+                    let copy = data[at..end].to_vec();
+                    data.splice(at..at, copy);
+                }
+                _ => data.insert(at, rng.next() as u8),
+            }
+        }
+
+        data.truncate(MAX_LEN);
+    }
+
+    #[test]
+    fn test_tokenizer_never_panics() {
+        let mut rng = Rng(3);
+
+        for _ in 0..CASES {
+            let input = match rng.below(2) {
+                0 => arbitrary_input(&mut rng),
+                _ => grammar_input(&mut rng),
+            };
+
+            for config in configs() {
+                Tokenizer::with_config(&input, config).for_each(drop);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parser_matches_reference() {
+        let mut rng = Rng(24);
+
+        for _ in 0..CASES {
+            let input = grammar_input(&mut rng);
+
+            assert_eq!(
+                (first_part(&input), second_part(&input)),
+                reference(&input),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_part_at_least_second_part() {
+        let mut rng = Rng(2024);
+
+        for _ in 0..CASES {
+            let input = grammar_input(&mut rng);
+
+            assert!(first_part(&input) >= second_part(&input), "{input:?}");
+        }
+    }
+
+    #[test]
+    #[ignore = "long-running fuzz loop"]
+    fn fuzz() {
+        let iterations = std::env::var("DAY03_FUZZ_ITERATIONS")
+            .ok()
+            .and_then(|iterations| iterations.parse().ok())
+            .unwrap_or(100_000);
+        let seed = std::env::var("DAY03_FUZZ_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                now.as_nanos() as u64
+            });
+
+        eprintln!("fuzzing {iterations} inputs with DAY03_FUZZ_SEED={seed}");
+
+        let mut rng = Rng(seed);
+        let mut corpus = vec![
+            include_bytes!("input_test").to_vec(),
+            include_bytes!("input_test2").to_vec(),
+        ];
+        corpus.extend((0..64).map(|_| grammar_input(&mut rng).into_bytes()));
+
+        for iteration in 0..iterations {
+            let mut data = corpus[rng.below(corpus.len())].clone();
+            mutate(&mut rng, &mut data);
+
+            if std::panic::catch_unwind(|| fuzz_target(&data)).is_err() {
+                panic!(
+                    "iteration {iteration} failed for DAY03_FUZZ_SEED={seed}: {:?}",
+                    String::from_utf8_lossy(&data)
+                );
+            }
+
+            if rng.below(100) == 0 {
+                corpus.push(data);
+            }
+        }
+    }
+}
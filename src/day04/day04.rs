@@ -1,251 +1,1087 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    hash::Hash,
+};
+
+use aoc2024::grid::{Cells, Coord, Grid, GridError, Ragged, Window, ALL_DIRECTIONS};
+
 fn main() {
     let input = include_str!("input");
-    println!("First part: {}", first_part(input));
-    println!("Second part: {}", second_part(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if args.is_empty() {
+        let grid = match build_grid(input) {
+            Ok(grid) => grid,
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        };
+
+        let x_mas = Pattern::parse(X_MAS).expect("valid pattern");
+
+        println!("First part: {}", first_part_matches(&grid).len());
+        println!("Second part: {}", second_part_matches(&grid, &x_mas).len());
+        return;
+    }
+
+    let options = match Options::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut glyphs = Glyphs::new(options.cells);
+
+    let grid = match &options.grid {
+        Some(path) => std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}")),
+        None => Ok(input.to_string()),
+    }
+    .and_then(|text| {
+        glyphs
+            .grid(&text, options.pad)
+            .map_err(|err| err.to_string())
+    });
+
+    let grid = match grid {
+        Ok(grid) => grid,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    let pattern = match options.pattern.as_deref().map(|template| {
+        Pattern::parse_with(template, options.cells, |cell| Some(glyphs.glyph(cell)))
+            .map_err(|err| format!("pattern {template}: {err}"))
+    }) {
+        Some(Ok(pattern)) => Some(pattern),
+        Some(Err(err)) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    if let (true, None, Some(style)) = (options.words.is_empty(), &pattern, options.show) {
+        let words = find_word(&grid, glyphs.encode(XMAS));
+        let x_mas = Pattern::parse_with(X_MAS, Cells::Chars, |cell| Some(glyphs.glyph(cell)))
+            .expect("valid pattern");
+        let crosses = second_part_matches(&grid, &x_mas);
+
+        println!("First part:");
+        print!("{}", render_words(&grid, &glyphs, &words, style));
+        println!("Second part:");
+        print!(
+            "{}",
+            render_patterns(&grid, &glyphs, &x_mas, &crosses, style)
+        );
+        return;
+    }
+
+    let encoded = options
+        .words
+        .iter()
+        .map(|word| glyphs.encode(word))
+        .collect::<Vec<_>>();
+    let counts = count_words(&grid, &encoded);
+
+    for (word, count) in options.words.iter().zip(counts) {
+        println!("{word}: {count}");
+    }
+
+    if let Some(pattern) = &pattern {
+        let matches = find_pattern(&grid, pattern, options.symmetry);
+
+        for pattern_match in &matches {
+            let (row, col) = pattern_match.top_left;
+            let transform = pattern_match.transform;
+
+            println!(
+                "{row},{col}: rotated {}°{}",
+                transform.quarter_turns as u32 * 90,
+                if transform.reflected {
+                    ", reflected"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        println!("{} matches", matches.len());
+
+        if let Some(style) = options.show {
+            print!(
+                "{}",
+                render_patterns(&grid, &glyphs, pattern, &matches, style)
+            );
+        }
+    }
+
+    if let (false, Some(style)) = (encoded.is_empty(), options.show) {
+        let matches = encoded
+            .iter()
+            .flat_map(|word| find_word(&grid, word.iter().copied()))
+            .collect::<Vec<_>>();
+
+        print!("{}", render_words(&grid, &glyphs, &matches, style));
+    }
 }
 
-trait Grid {
-    fn rows(&self) -> &Vec<Vec<char>>;
-    fn cols(&self) -> Vec<Vec<char>>;
-    fn diagonals(&self) -> Vec<Vec<char>>;
-    fn cnt_columns(&self) -> usize;
-    fn cnt_rows(&self) -> usize;
+/// Command line options for searching other words or patterns than the puzzle's.
+struct Options {
+    /// Read the grid from this file instead of the bundled input.
+    grid: Option<String>,
+    words: Vec<String>,
+    /// Template for `Pattern::parse`, parsed once the cells are known.
+    pattern: Option<String>,
+    symmetry: Symmetry,
+    cells: Cells,
+    /// Pad short lines of the grid instead of rejecting them.
+    pad: bool,
+    /// Draw the grid with only the matches, those of the puzzle if nothing else is searched.
+    show: Option<Style>,
+}
+
+impl Options {
+    /// Parses `--grid <path>`, `--words <word,word,..>` and `--dictionary <path>`
+    /// (one word per line), the last two may be repeated, as well as
+    /// `--pattern <template>` and `--symmetry <exact|rotations|reflections|all>`.
+    /// `--graphemes` makes every grapheme cluster a cell instead of every char
+    /// and `--ragged <reject|pad>` says what to do with lines of different lengths.
+    /// `--show <plain|ansi>` draws the matches.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            grid: None,
+            words: Vec::new(),
+            pattern: None,
+            symmetry: Symmetry::All,
+            cells: Cells::Chars,
+            pad: false,
+            show: None,
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+            match arg.as_str() {
+                "--grid" => options.grid = Some(value()?.clone()),
+                "--words" => options
+                    .words
+                    .extend(value()?.split(',').map(str::to_string)),
+                "--dictionary" => {
+                    let path = value()?;
+                    let dictionary =
+                        std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+
+                    options.words.extend(
+                        dictionary
+                            .lines()
+                            .map(str::trim)
+                            .filter(|word| !word.is_empty())
+                            .map(str::to_string),
+                    );
+                }
+                "--pattern" => options.pattern = Some(value()?.clone()),
+                "--symmetry" => {
+                    options.symmetry = match value()?.as_str() {
+                        "exact" => Symmetry::Exact,
+                        "rotations" => Symmetry::Rotations,
+                        "reflections" => Symmetry::Reflections,
+                        "all" => Symmetry::All,
+                        other => return Err(format!("invalid symmetry: {other}")),
+                    }
+                }
+                "--graphemes" => options.cells = Cells::Graphemes,
+                "--ragged" => {
+                    options.pad = match value()?.as_str() {
+                        "reject" => false,
+                        "pad" => true,
+                        other => return Err(format!("invalid ragged mode: {other}")),
+                    }
+                }
+                "--show" => {
+                    options.show = match value()?.as_str() {
+                        "plain" => Some(Style::Plain),
+                        "ansi" => Some(Style::Ansi),
+                        other => return Err(format!("invalid style: {other}")),
+                    }
+                }
+                other => return Err(format!("unknown argument: {other}")),
+            }
+        }
+
+        options.words.retain(|word| !word.trim().is_empty());
+
+        if options.words.is_empty() && options.pattern.is_none() && options.show.is_none() {
+            return Err(
+                "nothing to search for, use --words, --dictionary, --pattern or --show".to_string(),
+            );
+        }
+
+        Ok(options)
+    }
 }
 
-struct GridScanner<'g> {
+/// Slides a `rows` x `cols` window over the grid, row by row.
+struct GridScanner<'g, T> {
     curr_row: usize,
     curr_col: usize,
-    grid: &'g Vec<Vec<char>>,
+    rows: usize,
+    cols: usize,
+    grid: &'g Grid<T>,
 }
 
-impl<'g> GridScanner<'g> {
-    fn new(grid: &'g Vec<Vec<char>>) -> Self {
+impl<'g, T> GridScanner<'g, T> {
+    fn new(grid: &'g Grid<T>, rows: usize, cols: usize) -> Self {
         Self {
             curr_col: 0,
             curr_row: 0,
+            rows,
+            cols,
             grid,
         }
     }
 }
 
-impl Iterator for GridScanner<'_> {
-    type Item = Vec<Vec<char>>;
+impl<'g, T> Iterator for GridScanner<'g, T> {
+    type Item = Window<'g, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_col + 3 <= self.grid.cnt_columns() && self.curr_row + 3 <= self.grid.cnt_rows() {
-            let first_row = self.grid[self.curr_row][self.curr_col..self.curr_col + 3].to_vec();
-            let second_row =
-                self.grid[self.curr_row + 1][self.curr_col..self.curr_col + 3].to_vec();
-            let third_row = self.grid[self.curr_row + 2][self.curr_col..self.curr_col + 3].to_vec();
+        let top_left = (self.curr_row, self.curr_col);
+        let result = self.grid.window(top_left, self.rows, self.cols)?;
+
+        self.curr_col += 1;
+
+        if self.curr_col + self.cols > self.grid.cnt_columns() {
+            self.curr_row += 1;
+            self.curr_col = 0;
+        }
+
+        Some(result)
+    }
+}
+
+/// Which orientations of a `Pattern` to look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    /// Only as written.
+    Exact,
+    /// All four quarter turns.
+    Rotations,
+    /// As written and mirrored left to right.
+    Reflections,
+    /// Every quarter turn, mirrored or not.
+    All,
+}
+
+/// How a pattern was turned to match: first mirrored left to right if
+/// `reflected`, then turned clockwise `quarter_turns` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transform {
+    quarter_turns: u8,
+    reflected: bool,
+}
+
+/// A rectangular template of cells where `None` matches any cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern<T> {
+    cells: Grid<Option<T>>,
+}
+
+impl Pattern<char> {
+    /// Parses a template with rows separated by `/` or newlines and `.` as wildcard,
+    /// e.g. `M.S/.A./M.S`.
+    fn parse(template: &str) -> Result<Self, GridError> {
+        let cells = Grid::parse(&template.replace('/', "\n"), |c| (c != '.').then_some(c))?;
+
+        Ok(Self { cells })
+    }
+}
+
+impl<T: Copy + PartialEq> Pattern<T> {
+    /// Like `parse`, but splitting the rows into `cells` and mapping every one
+    /// but the wildcards through `cell`.
+    fn parse_with(
+        template: &str,
+        cells: Cells,
+        mut cell: impl FnMut(&str) -> T,
+    ) -> Result<Self, GridError> {
+        let template = template.replace('/', "\n");
+        let cells = Grid::parse_with(&template, cells, Ragged::Reject, |text| {
+            (text != ".").then(|| cell(text))
+        })?;
+
+        Ok(Self { cells })
+    }
+
+    fn rotated(&self) -> Self {
+        let rows = self.cells.cnt_rows();
+        let cells = Grid::from_fn(self.cells.cnt_columns(), rows, |(row, col)| {
+            self.cells[(rows - 1 - col, row)]
+        });
+
+        Self { cells }
+    }
 
-            let result = vec![first_row, second_row, third_row];
+    fn reflected(&self) -> Self {
+        let cols = self.cells.cnt_columns();
+        let cells = Grid::from_fn(self.cells.cnt_rows(), cols, |(row, col)| {
+            self.cells[(row, cols - 1 - col)]
+        });
 
-            self.curr_col += 1;
+        Self { cells }
+    }
+
+    /// The distinct orientations allowed by `symmetry`, each with the first
+    /// transform producing it.
+    fn variants(&self, symmetry: Symmetry) -> Vec<(Transform, Self)> {
+        let (quarter_turns, reflections) = match symmetry {
+            Symmetry::Exact => (1, 1),
+            Symmetry::Rotations => (4, 1),
+            Symmetry::Reflections => (1, 2),
+            Symmetry::All => (4, 2),
+        };
+
+        let mut variants: Vec<(Transform, Self)> = Vec::new();
 
-            if self.curr_col + 3 > self.grid.cnt_columns() {
-                self.curr_row += 1;
-                self.curr_col = 0;
+        for reflected in [false, true].into_iter().take(reflections) {
+            let mut variant = if reflected {
+                self.reflected()
+            } else {
+                self.clone()
+            };
+
+            for quarter_turns in 0..quarter_turns {
+                if !variants.iter().any(|(_, known)| *known == variant) {
+                    let transform = Transform {
+                        quarter_turns,
+                        reflected,
+                    };
+                    variants.push((transform, variant.clone()));
+                }
+
+                variant = variant.rotated();
             }
+        }
 
-            Some(result)
+        variants
+    }
+
+    /// The orientation `transform` turns this pattern into.
+    fn transformed(&self, transform: Transform) -> Self {
+        let mut pattern = if transform.reflected {
+            self.reflected()
         } else {
-            None
+            self.clone()
+        };
+
+        for _ in 0..transform.quarter_turns {
+            pattern = pattern.rotated();
         }
+
+        pattern
+    }
+
+    fn matches(&self, window: &Window<T>) -> bool {
+        self.cells
+            .coords()
+            .all(|coord| self.cells[coord].is_none_or(|c| window[coord] == c))
     }
 }
 
-impl Grid for Vec<Vec<char>> {
-    fn cnt_rows(&self) -> usize {
-        self.len()
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PatternMatch {
+    top_left: Coord,
+    transform: Transform,
+}
+
+impl PatternMatch {
+    /// The cells matched by the non-wildcard cells of `pattern`, which this was found for.
+    fn cells<T: Copy + PartialEq>(&self, pattern: &Pattern<T>) -> Vec<Coord> {
+        let variant = pattern.transformed(self.transform);
+        let (top, left) = self.top_left;
+
+        variant
+            .cells
+            .coords()
+            .filter(|coord| variant.cells[*coord].is_some())
+            .map(|(row, col)| (top + row, left + col))
+            .collect()
     }
-    fn cnt_columns(&self) -> usize {
-        if let Some(row) = self.first() {
-            row.len()
-        } else {
-            0
+}
+
+/// Every position where an orientation of `pattern` fits, ordered by position.
+fn find_pattern<T: Copy + PartialEq>(
+    grid: &Grid<T>,
+    pattern: &Pattern<T>,
+    symmetry: Symmetry,
+) -> Vec<PatternMatch> {
+    let mut matches = pattern
+        .variants(symmetry)
+        .into_iter()
+        .filter(|(_, variant)| variant.cells.cnt_rows() > 0 && variant.cells.cnt_columns() > 0)
+        .flat_map(|(transform, variant)| {
+            let (rows, cols) = (variant.cells.cnt_rows(), variant.cells.cnt_columns());
+
+            GridScanner::new(grid, rows, cols)
+                .filter(move |window| variant.matches(window))
+                .map(move |window| PatternMatch {
+                    top_left: window.top_left(),
+                    transform,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by_key(|pattern_match| pattern_match.top_left);
+    matches
+}
+
+pub fn build_grid(input: &str) -> Result<Grid<char>, GridError> {
+    Grid::parse(input, |c| c)
+}
+
+/// How `render` shows the cells that are part of a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    /// As they are, like the illustrations in the puzzle.
+    Plain,
+    /// In an ANSI colour per direction or orientation of the match.
+    Ansi,
+}
+
+/// ANSI foreground colours: red, green, yellow, blue, magenta, cyan, bright red, bright blue.
+const COLOURS: [u8; 8] = [31, 32, 33, 34, 35, 36, 91, 94];
+
+/// Draws the grid one line per row with every cell not in `marks` replaced by
+/// `.`. Every mark is a cell and the colour used for it with `Style::Ansi`,
+/// the last one winning for a cell marked more than once.
+fn render(
+    grid: &Grid<Option<Glyph>>,
+    glyphs: &Glyphs,
+    marks: impl IntoIterator<Item = (Coord, usize)>,
+    style: Style,
+) -> String {
+    let mut colours = Grid::from_fn(grid.cnt_rows(), grid.cnt_columns(), |_| None);
+
+    for (coord, colour) in marks {
+        colours[coord] = Some(COLOURS[colour % COLOURS.len()]);
+    }
+
+    let mut out = String::new();
+
+    for row in 0..grid.cnt_rows() {
+        for (col, glyph) in grid.row(row).iter().enumerate() {
+            let text = glyph.map_or(" ", |glyph| glyphs.decode(glyph));
+
+            match (colours[(row, col)], style) {
+                (None, _) => out.push('.'),
+                (Some(_), Style::Plain) => out.push_str(text),
+                (Some(colour), Style::Ansi) => {
+                    let _ = write!(out, "\x1b[{colour}m{text}\x1b[0m");
+                }
+            }
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Renders the cells of word matches, coloured by direction.
+fn render_words(
+    grid: &Grid<Option<Glyph>>,
+    glyphs: &Glyphs,
+    matches: &[WordMatch],
+    style: Style,
+) -> String {
+    let marks = matches.iter().flat_map(|word_match| {
+        let colour = word_match.direction as usize;
+        word_match.cells.iter().map(move |cell| (*cell, colour))
+    });
+
+    render(grid, glyphs, marks, style)
+}
+
+/// Renders the cells of pattern matches, coloured by orientation.
+fn render_patterns(
+    grid: &Grid<Option<Glyph>>,
+    glyphs: &Glyphs,
+    pattern: &Pattern<Option<Glyph>>,
+    matches: &[PatternMatch],
+    style: Style,
+) -> String {
+    let marks = matches.iter().flat_map(|pattern_match| {
+        let transform = pattern_match.transform;
+        let colour = transform.quarter_turns as usize + 4 * transform.reflected as usize;
+
+        pattern_match
+            .cells(pattern)
+            .into_iter()
+            .map(move |cell| (cell, colour))
+    });
+
+    render(grid, glyphs, marks, style)
+}
+
+/// A distinct cell of a grid read for the command line, a char or a grapheme
+/// cluster, numbered by `Glyphs` in the order first seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Glyph(u32);
+
+/// Numbers every distinct cell of a grid split into `Cells`, so the searches
+/// work on grapheme clusters as well as on chars.
+#[derive(Debug)]
+struct Glyphs {
+    cells: Cells,
+    clusters: Vec<String>,
+    glyphs: HashMap<String, Glyph>,
+}
+
+impl Glyphs {
+    fn new(cells: Cells) -> Self {
+        Self {
+            cells,
+            clusters: Vec::new(),
+            glyphs: HashMap::new(),
         }
     }
-    fn rows(&self) -> &Vec<Vec<char>> {
-        self
+
+    fn glyph(&mut self, cluster: &str) -> Glyph {
+        if let Some(glyph) = self.glyphs.get(cluster) {
+            return *glyph;
+        }
+
+        let glyph = Glyph(u32::try_from(self.clusters.len()).expect("fewer than 2^32 clusters"));
+
+        self.clusters.push(cluster.to_string());
+        self.glyphs.insert(cluster.to_string(), glyph);
+        glyph
     }
-    fn cols(&self) -> Vec<Vec<char>> {
-        (0..self.cnt_columns())
+
+    /// The glyphs of `text` split into cells, to search for in a grid of glyphs.
+    fn encode(&mut self, text: &str) -> Vec<Option<Glyph>> {
+        self.cells
+            .split(text)
             .into_iter()
-            .map(|col_idx| {
-                self.iter()
-                    .map(|row| row[col_idx].clone())
-                    .collect::<Vec<_>>()
-            })
+            .map(|cluster| Some(self.glyph(cluster)))
             .collect()
     }
-    fn diagonals(&self) -> Vec<Vec<char>> {
-        let top_forward_diagonals = (0..self.cnt_columns())
-            .map(|col_idx| {
-                (0..self.cnt_rows())
-                    .flat_map(|row_idx| {
-                        let new_col_idx = row_idx + col_idx;
-                        if new_col_idx < self.cnt_columns() {
-                            Some(self[row_idx][new_col_idx].clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
 
-        let top_backward_diagonals = (0..self.cnt_columns())
-            .into_iter()
-            .rev()
-            .map(|col_idx| {
-                (0..self.cnt_rows())
-                    .flat_map(|row_idx| {
-                        let new_col_idx = col_idx.checked_sub(row_idx);
-                        if let Some(new_col_idx) = new_col_idx {
-                            Some(self[row_idx][new_col_idx].clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+    /// The text `glyph` stands for.
+    fn decode(&self, glyph: Glyph) -> &str {
+        &self.clusters[glyph.0 as usize]
+    }
 
-        let side_forward_diagonals = (1..self.cnt_rows())
-            .into_iter()
-            .map(|row_idx| {
-                (0..self.cnt_columns())
-                    .into_iter()
-                    .flat_map(|col_idx| {
-                        let new_row_idx = row_idx + col_idx;
-                        if col_idx < self.cnt_columns() && new_row_idx < self.cnt_rows() {
-                            Some(self[new_row_idx][col_idx])
-                        } else {
-                            None
-                        }
+    /// Reads `text` into a grid of glyphs. Short lines are padded with `None`,
+    /// which only pattern wildcards match, if `pad`, and rejected otherwise.
+    fn grid(&mut self, text: &str, pad: bool) -> Result<Grid<Option<Glyph>>, GridError> {
+        let ragged = if pad {
+            Ragged::Pad(None)
+        } else {
+            Ragged::Reject
+        };
+
+        Grid::parse_with(text, self.cells, ragged, |cell| Some(self.glyph(cell)))
+    }
+}
+
+/// The eight directions a word can run in, clockwise starting upwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    fn offset(self) -> (isize, isize) {
+        ALL_DIRECTIONS[self as usize]
+    }
+}
+
+/// A word found in the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WordMatch {
+    start: Coord,
+    direction: Direction,
+    /// The cells spelling the word, in order.
+    cells: Vec<Coord>,
+}
+
+/// Finds every occurrence of `word` in all eight directions, ordered by start
+/// cell (row by row) and then by direction.
+fn find_word<T: Copy + PartialEq>(
+    grid: &Grid<T>,
+    word: impl IntoIterator<Item = T>,
+) -> Vec<WordMatch> {
+    let word = &word.into_iter().collect::<Vec<_>>();
+
+    let Some(first) = word.first() else {
+        return Vec::new();
+    };
+
+    grid.coords()
+        .filter(|start| grid[*start] == *first)
+        .flat_map(|start| {
+            Direction::ALL.into_iter().filter_map(move |direction| {
+                let line = grid.line(start, direction.offset());
+
+                if line.len() < word.len() || !line.take(word.len()).eq(word) {
+                    return None;
+                }
+
+                let cells = (0..word.len() as isize)
+                    .map(|step| {
+                        let (d_row, d_col) = direction.offset();
+                        (
+                            start.0.wrapping_add_signed(d_row * step),
+                            start.1.wrapping_add_signed(d_col * step),
+                        )
                     })
-                    .collect::<Vec<_>>()
+                    .collect();
+
+                Some(WordMatch {
+                    start,
+                    direction,
+                    cells,
+                })
             })
-            .collect::<Vec<_>>();
+        })
+        .collect()
+}
 
-        let side_backward_diagonals = (1..self.cnt_rows())
-            .into_iter()
-            .map(|row_idx| {
-                (0..self.cnt_columns())
-                    .into_iter()
-                    .rev()
-                    .enumerate()
-                    .flat_map(|(step, col_idx)| {
-                        let new_row_idx = row_idx + step;
-                        if col_idx < self.cnt_columns() && new_row_idx < self.cnt_rows() {
-                            Some(self[new_row_idx][col_idx])
-                        } else {
-                            None
+/// Trie node of a `Dictionary`, with transitions only for the cells that
+/// follow it in some word.
+#[derive(Debug)]
+struct DictionaryNode<T> {
+    children: HashMap<T, usize>,
+    fail: usize,
+    /// Indices of the words ending here, including those reached through fail links.
+    words: Vec<usize>,
+}
+
+impl<T> Default for DictionaryNode<T> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            fail: 0,
+            words: Vec::new(),
+        }
+    }
+}
+
+/// Aho-Corasick automaton over a list of words and their reversals, finding
+/// all of them in a line of cells in a single pass.
+///
+/// Every node keeps only its own children, and a cell without one falls back
+/// along the fail links, so the size follows the words and not their alphabet.
+#[derive(Debug)]
+struct Dictionary<T> {
+    nodes: Vec<DictionaryNode<T>>,
+}
+
+impl<T: Copy + Eq + Hash> Dictionary<T> {
+    fn new(words: &[impl AsRef<[T]>]) -> Self {
+        let mut nodes = vec![DictionaryNode::default()];
+
+        for (idx, word) in words
+            .iter()
+            .map(AsRef::as_ref)
+            .enumerate()
+            .filter(|(_, word)| !word.is_empty())
+        {
+            for spelling in [word.to_vec(), word.iter().rev().copied().collect()] {
+                let mut node = 0;
+
+                for c in spelling {
+                    node = match nodes[node].children.get(&c) {
+                        Some(&next) => next,
+                        None => {
+                            nodes.push(DictionaryNode::default());
+                            let next = nodes.len() - 1;
+                            nodes[node].children.insert(c, next);
+                            next
                         }
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+                    };
+                }
 
-        top_forward_diagonals
-            .into_iter()
-            .chain(top_backward_diagonals)
-            .chain(side_forward_diagonals)
-            .chain(side_backward_diagonals)
-            .collect()
+                nodes[node].words.push(idx);
+            }
+        }
+
+        let mut dictionary = Self { nodes };
+        let mut queue = VecDeque::from([0]);
+
+        // breadth first, so every fail link points to a node that's done already
+        while let Some(node) = queue.pop_front() {
+            let children = dictionary.nodes[node]
+                .children
+                .iter()
+                .map(|(c, child)| (*c, *child))
+                .collect::<Vec<_>>();
+
+            for (c, child) in children {
+                // the root's children fail back to the root, everyone else follows
+                // the parent's fail link as far as it goes
+                let fail = if node == 0 {
+                    0
+                } else {
+                    dictionary.step(dictionary.nodes[node].fail, &c)
+                };
+                let inherited = dictionary.nodes[fail].words.clone();
+
+                dictionary.nodes[child].fail = fail;
+                dictionary.nodes[child].words.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        dictionary
+    }
+
+    /// The node reached from `node` on `c`, falling back along the fail links
+    /// until one has a child for it.
+    fn step(&self, mut node: usize, c: &T) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].children.get(c) {
+                return next;
+            }
+
+            if node == 0 {
+                return 0;
+            }
+
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Adds the number of hits in `line` to `counts`, which is indexed like the word list.
+    fn scan<'a>(&self, line: impl Iterator<Item = &'a T>, counts: &mut [usize])
+    where
+        T: 'a,
+    {
+        let mut node = 0;
+
+        for c in line {
+            node = self.step(node, c);
+
+            for word in &self.nodes[node].words {
+                counts[*word] += 1;
+            }
+        }
     }
 }
 
-fn build_grid(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+/// Counts how often each word occurs in any of the eight directions, scanning
+/// every row, column and diagonal once for the whole list, in its order.
+fn count_words<T: Copy + Eq + Hash>(grid: &Grid<T>, words: &[impl AsRef<[T]>]) -> Vec<usize> {
+    let dictionary = Dictionary::new(words);
+    let mut counts = vec![0; words.len()];
+
+    for row in grid.rows() {
+        dictionary.scan(row.iter(), &mut counts);
+    }
+
+    for line in grid.cols().chain(grid.diagonals()) {
+        dictionary.scan(line, &mut counts);
+    }
+
+    counts
 }
 
-fn first_part(input: &str) -> u64 {
-    let grid = build_grid(input);
-    const SEARCH_TERM: &str = "XMAS";
-    let search_len = SEARCH_TERM.len();
-    let count_row_hits: usize = grid
-        .rows()
-        .iter()
-        .map(|row| {
-            row.windows(search_len)
-                .filter(|window| {
-                    window.iter().collect::<String>() == SEARCH_TERM.to_string()
-                        || window.iter().collect::<String>()
-                            == SEARCH_TERM.chars().rev().collect::<String>()
-                })
-                .count()
-        })
-        .sum();
-    let count_col_hits: usize = grid
-        .cols()
-        .iter()
-        .map(|col| {
-            col.windows(search_len)
-                .filter(|window| {
-                    window.iter().collect::<String>() == SEARCH_TERM.to_string()
-                        || window.iter().collect::<String>()
-                            == SEARCH_TERM.chars().rev().collect::<String>()
-                })
-                .count()
-        })
-        .sum();
-    let diag_hits: usize = grid
-        .diagonals()
-        .iter()
-        .filter(|diag| diag.len() >= SEARCH_TERM.len())
-        .map(|diag| {
-            diag.windows(search_len)
-                .filter(|window| {
-                    window.iter().collect::<String>() == SEARCH_TERM.to_string()
-                        || window.iter().collect::<String>()
-                            == SEARCH_TERM.chars().rev().collect::<String>()
-                })
-                .count()
-        })
-        .sum();
+const XMAS: &str = "XMAS";
 
-    (count_row_hits + count_col_hits + diag_hits) as u64
+/// Two crossing diagonal MAS, each of which may be read backwards.
+const X_MAS: &str = "M.S/.A./M.S";
+
+fn first_part_matches(grid: &Grid<char>) -> Vec<WordMatch> {
+    find_word(grid, XMAS.chars())
 }
 
-fn second_part(input: &str) -> u64 {
-    let grid = build_grid(input);
-    const SEARCH_TERM: &str = "MAS";
+/// The X-MAS found by `x_mas`, one per position even if several orientations
+/// fit there.
+fn second_part_matches<T: Copy + PartialEq>(
+    grid: &Grid<T>,
+    x_mas: &Pattern<T>,
+) -> Vec<PatternMatch> {
+    let mut matches = find_pattern(grid, x_mas, Symmetry::All);
+    matches.dedup_by_key(|pattern_match| pattern_match.top_left);
 
-    let scanner = GridScanner::new(&grid);
+    matches
+}
 
-    let result = scanner
-        .into_iter()
-        .filter(|window| {
-            let diags = window.diagonals();
-            diags.iter().filter(|diag| diag.len() == SEARCH_TERM.len()).all(|diag| {
-                diag.iter().collect::<String>() == SEARCH_TERM.to_string()
-                    || diag.iter().collect::<String>()
-                        == SEARCH_TERM.chars().rev().collect::<String>()
-            })
-        })
-        .count();
+pub fn first_part(input: &str) -> Result<u64, GridError> {
+    Ok(first_part_matches(&build_grid(input)?).len() as u64)
+}
+
+pub fn second_part(input: &str) -> Result<u64, GridError> {
+    let x_mas = Pattern::parse(X_MAS).expect("valid pattern");
 
-    result as u64
+    Ok(second_part_matches(&build_grid(input)?, &x_mas).len() as u64)
 }
 
+#[cfg(test)]
+#[path = "../mock/temp_dir.rs"]
+mod temp_dir;
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use std::collections::HashSet;
+    use temp_dir::TempDir;
 
     #[test]
     fn test_first_part() {
         let input = include_str!("input_test");
 
-        assert_eq!(first_part(input), 18);
+        assert_eq!(first_part(input), Ok(18));
+        assert_eq!(
+            first_part("XMAS\nXM\n"),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 4,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_word() {
+        let grid = build_grid("XMAS\nMM.A\nA.AM\nS..X").unwrap();
+        let matches = find_word(&grid, "XMAS".chars());
+
+        let expected = vec![
+            WordMatch {
+                start: (0, 0),
+                direction: Direction::Right,
+                cells: vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+            },
+            WordMatch {
+                start: (0, 0),
+                direction: Direction::Down,
+                cells: vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+            },
+            WordMatch {
+                start: (3, 3),
+                direction: Direction::Up,
+                cells: vec![(3, 3), (2, 3), (1, 3), (0, 3)],
+            },
+        ];
+
+        assert_eq!(matches, expected);
+        assert!(find_word(&grid, "".chars()).is_empty());
+        assert_eq!(
+            find_word(&grid, "X".chars()).len(),
+            2 * Direction::ALL.len()
+        );
+    }
+
+    #[test]
+    fn test_find_word_directions() {
+        let grid = build_grid(include_str!("input_test")).unwrap();
+        let matches = find_word(&grid, "XMAS".chars());
+
+        let count = |direction| {
+            matches
+                .iter()
+                .filter(|word_match| word_match.direction == direction)
+                .count()
+        };
+
+        assert_eq!(matches.len(), 18);
+        assert_eq!(count(Direction::Right), 3);
+        assert_eq!(count(Direction::Left), 2);
+        assert_eq!(count(Direction::Down), 1);
+        assert_eq!(count(Direction::Up), 2);
+        assert_eq!(count(Direction::DownRight) + count(Direction::UpLeft), 5);
+        assert_eq!(count(Direction::DownLeft) + count(Direction::UpRight), 5);
+        assert!(matches.iter().all(|word_match| word_match
+            .cells
+            .iter()
+            .map(|cell| grid[*cell])
+            .eq("XMAS".chars())));
+    }
+
+    #[test]
+    fn test_count_words() {
+        let grid = build_grid(include_str!("input_test")).unwrap();
+        let words = ["XMAS", "MAS", "SAM", "AMA", "X", "", "ZZZ"];
+        let counts = count_words(&grid, &words.map(|word| word.chars().collect::<Vec<_>>()));
+
+        for (word, count) in words.iter().zip(&counts) {
+            assert_eq!(*count, find_word(&grid, word.chars()).len(), "{word}");
+        }
+
+        assert_eq!(counts[0], 18);
+        assert_eq!(counts[1], counts[2]);
+        assert_eq!(counts[6], 0);
+    }
+
+    #[test]
+    fn test_dictionary_overlapping_words() {
+        let words = ["SHE", "HE", "HERS", "E"].map(|word| word.chars().collect::<Vec<_>>());
+        let dictionary = Dictionary::new(&words);
+        let mut counts = vec![0; 4];
+
+        dictionary.scan(['U', 'S', 'H', 'E', 'R', 'S'].iter(), &mut counts);
+
+        // a single char reads the same both ways, so it's counted once per direction
+        assert_eq!(counts, vec![1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_count_words_large_alphabet() {
+        // thousands of CJK ideographs, too many for a transition table over the alphabet
+        let ideograph = |n: u32| char::from_u32(0x4e00 + n).unwrap();
+        let mut seed = 7_u32;
+        let mut random = |range: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % range
+        };
+
+        let text = (0..24)
+            .map(|_| (0..24).map(|_| ideograph(random(40))).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let grid = build_grid(&text).unwrap();
+
+        let mut words = (0..6000)
+            .map(|n| vec![ideograph(n), ideograph((n * 7 + 1) % 6000)])
+            .collect::<Vec<_>>();
+        words.extend((0..500).map(|_| {
+            let len = 1 + random(4) as usize;
+            (0..len).map(|_| ideograph(random(40))).collect()
+        }));
+
+        let counts = count_words(&grid, &words);
+
+        for (word, count) in words.iter().zip(&counts) {
+            assert_eq!(
+                *count,
+                find_word(&grid, word.iter().copied()).len(),
+                "{}",
+                word.iter().collect::<String>()
+            );
+        }
+
+        assert!(counts.iter().filter(|count| **count > 0).count() > 100);
+    }
+
+    #[test]
+    fn test_glyphs() {
+        // "é" as "e" and a combining accent, next to a precomposed one and a
+        // private use char
+        let text = "ce\u{301}\u{e9}e\u{301}\u{F0000}";
+        let mut glyphs = Glyphs::new(Cells::Graphemes);
+        let encoded = glyphs.encode(text);
+
+        assert_eq!(encoded.len(), 5);
+        assert_eq!(encoded[1], encoded[3]);
+        assert_eq!(
+            encoded.iter().flatten().collect::<HashSet<_>>().len(),
+            4,
+            "{encoded:?}"
+        );
+        assert_eq!(
+            encoded
+                .iter()
+                .flatten()
+                .map(|glyph| glyphs.decode(*glyph))
+                .collect::<String>(),
+            text
+        );
+
+        let mut chars = Glyphs::new(Cells::Chars);
+        assert_eq!(chars.encode(text).len(), text.chars().count());
+    }
+
+    #[test]
+    fn test_graphemes_and_padding() {
+        // the accents only count as their own cells if split into chars
+        let text = "CAFE\u{301}\nA\nF\nE\u{301}";
+        let mut glyphs = Glyphs::new(Cells::Graphemes);
+        let grid = glyphs.grid(text, true).unwrap();
+        let word = glyphs.encode("CAFE\u{301}");
+
+        assert_eq!(grid.cnt_columns(), 4);
+        assert_eq!(grid[(1, 1)], None);
+        assert_eq!(find_word(&grid, word.iter().copied()).len(), 2);
+        assert_eq!(count_words(&grid, &[&word]), vec![2]);
+        assert!(find_word(&grid, glyphs.encode("CAFE")).is_empty());
+
+        // padding is no cell of the text, not even a NUL
+        assert!(find_word(&grid, glyphs.encode("A\0")).is_empty());
+        let pattern = Pattern::parse_with("A.", Cells::Graphemes, |cell| Some(glyphs.glyph(cell)));
+        assert_eq!(
+            find_pattern(&grid, &pattern.unwrap(), Symmetry::Exact).len(),
+            2
+        );
+
+        assert!(glyphs.grid(text, false).is_err());
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let args = [
+            "--words", "XMAS,SAM", "--grid", "grid.txt", "--words", "MAS",
+        ]
+        .map(String::from);
+        let options = Options::from_args(&args).unwrap();
+
+        assert_eq!(options.grid.as_deref(), Some("grid.txt"));
+        assert_eq!(options.words, vec!["XMAS", "SAM", "MAS"]);
+
+        assert_eq!(options.cells, Cells::Chars);
+        assert!(!options.pad);
+
+        let args = ["--pattern", "M.S", "--graphemes", "--ragged", "pad"].map(String::from);
+        let options = Options::from_args(&args).unwrap();
+
+        assert_eq!(options.pattern.as_deref(), Some("M.S"));
+        assert_eq!(options.cells, Cells::Graphemes);
+        assert!(options.pad);
+
+        assert!(Options::from_args(&["--ragged".to_string(), "trim".to_string()]).is_err());
+        assert!(Options::from_args(&["--grid".to_string()]).is_err());
+        assert!(Options::from_args(&["--words".to_string(), ",".to_string()]).is_err());
+
+        let dir = TempDir::new("dictionary");
+        let path = dir.path().join("words");
+        std::fs::write(&path, "XMAS\r\n\r\n  SAM \r\nMAS").unwrap();
+        let args = ["--dictionary".to_string(), path.display().to_string()];
+        let options = Options::from_args(&args);
+
+        assert_eq!(options.unwrap().words, vec!["XMAS", "SAM", "MAS"]);
     }
 
     #[test]
     fn test_grid() {
         let small_grid = "ABCDE\n12345\nDEFGH\n67890";
 
-        let grid = build_grid(small_grid);
+        let grid = build_grid(small_grid).unwrap();
 
         let expected_rows = vec![
             ['A', 'B', 'C', 'D', 'E'],
@@ -254,7 +1090,7 @@ mod tests {
             ['6', '7', '8', '9', '0'],
         ];
 
-        assert_eq!(grid.rows(), &expected_rows);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), expected_rows);
 
         let expected_cols = vec![
             ['A', '1', 'D', '6'],
@@ -264,7 +1100,12 @@ mod tests {
             ['E', '5', 'H', '0'],
         ];
 
-        assert_eq!(grid.cols(), expected_cols);
+        let cols = grid
+            .cols()
+            .map(|col| col.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(cols, expected_cols);
 
         let expected_diagonals = vec![
             vec!['A', '2', 'F', '9'],
@@ -285,68 +1126,358 @@ mod tests {
             vec!['0'],
         ];
 
-        assert_eq!(grid.diagonals(), expected_diagonals);
+        let diagonals = grid
+            .diagonals()
+            .map(|diag| diag.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(diagonals, expected_diagonals);
     }
 
     #[test]
     fn test_grid_scanner() {
         let grid = "ABCDE\nFGHIJ\nKLMNO\nPQRST";
-        let grid = build_grid(grid);
-        let mut scanner = GridScanner::new(&grid);
+        let grid = build_grid(grid).unwrap();
+        let mut scanner = GridScanner::new(&grid, 3, 3);
 
         let first = scanner.next().unwrap();
+        assert_eq!(first.top_left(), (0, 0));
         let expected_first_rows = vec![
             vec!['A', 'B', 'C'],
             vec!['F', 'G', 'H'],
             vec!['K', 'L', 'M'],
         ];
-        assert_eq!(first.rows(), &expected_first_rows);
+        assert_eq!(first.rows().collect::<Vec<_>>(), expected_first_rows);
 
         let second = scanner.next().unwrap();
+        assert_eq!(second.top_left(), (0, 1));
         let expected_second_rows = vec![
             vec!['B', 'C', 'D'],
             vec!['G', 'H', 'I'],
             vec!['L', 'M', 'N'],
         ];
-        assert_eq!(second.rows(), &expected_second_rows);
+        assert_eq!(second.rows().collect::<Vec<_>>(), expected_second_rows);
 
         let third = scanner.next().unwrap();
+        assert_eq!(third.top_left(), (0, 2));
         let expected_third_rows = vec![
             vec!['C', 'D', 'E'],
             vec!['H', 'I', 'J'],
             vec!['M', 'N', 'O'],
         ];
-        assert_eq!(third.rows(), &expected_third_rows);
+        assert_eq!(third.rows().collect::<Vec<_>>(), expected_third_rows);
 
         let fourth = scanner.next().unwrap();
+        assert_eq!(fourth.top_left(), (1, 0));
         let expected_fourth_rows = vec![
             vec!['F', 'G', 'H'],
             vec!['K', 'L', 'M'],
             vec!['P', 'Q', 'R'],
         ];
-        assert_eq!(fourth.rows(), &expected_fourth_rows);
+        assert_eq!(fourth.rows().collect::<Vec<_>>(), expected_fourth_rows);
 
         let fifth = scanner.next().unwrap();
+        assert_eq!(fifth.top_left(), (1, 1));
         let expected_fifth_rows = vec![
             vec!['G', 'H', 'I'],
             vec!['L', 'M', 'N'],
             vec!['Q', 'R', 'S'],
         ];
-        assert_eq!(fifth.rows(), &expected_fifth_rows);
+        assert_eq!(fifth.rows().collect::<Vec<_>>(), expected_fifth_rows);
 
         let sixth = scanner.next().unwrap();
+        assert_eq!(sixth.top_left(), (1, 2));
         let expected_sixth_rows = vec![
             vec!['H', 'I', 'J'],
             vec!['M', 'N', 'O'],
             vec!['R', 'S', 'T'],
         ];
-        assert_eq!(sixth.rows(), &expected_sixth_rows);
+        assert_eq!(sixth.rows().collect::<Vec<_>>(), expected_sixth_rows);
+
+        assert!(scanner.next().is_none());
+
+        let mut scanner = GridScanner::new(&grid, 2, 5);
+        let window = scanner.next().unwrap();
+        assert_eq!(
+            window.rows().collect::<Vec<_>>(),
+            vec![
+                "ABCDE".chars().collect::<Vec<_>>(),
+                "FGHIJ".chars().collect()
+            ]
+        );
+        assert_eq!(scanner.count(), 2);
+    }
+
+    #[test]
+    fn test_pattern_variants() {
+        let pattern = Pattern::parse("M.S/.A./M.S").unwrap();
+        let variants = pattern.variants(Symmetry::All);
+
+        assert_eq!(variants.len(), 4);
+        assert_eq!(variants[1].1, Pattern::parse("M.M\n.A.\nS.S").unwrap());
+        assert!(variants.iter().all(|(transform, _)| !transform.reflected));
+
+        let pattern = Pattern::parse("XM/..").unwrap();
+
+        assert_eq!(pattern.variants(Symmetry::Exact).len(), 1);
+        assert_eq!(pattern.variants(Symmetry::Rotations).len(), 4);
+        assert_eq!(pattern.variants(Symmetry::Reflections).len(), 2);
+        assert_eq!(pattern.variants(Symmetry::All).len(), 8);
+        assert_eq!(pattern.rotated(), Pattern::parse(".X/.M").unwrap());
+        assert_eq!(pattern.reflected(), Pattern::parse("MX/..").unwrap());
+        assert!(Pattern::parse("AB/C").is_err());
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let grid = build_grid("XMAS\nSAMX\nMASX").unwrap();
+        let pattern = Pattern::parse("XM.").unwrap();
+
+        let exact = find_pattern(&grid, &pattern, Symmetry::Exact);
+        assert_eq!(
+            exact,
+            vec![PatternMatch {
+                top_left: (0, 0),
+                transform: Transform {
+                    quarter_turns: 0,
+                    reflected: false
+                }
+            }]
+        );
+
+        let mirrored = find_pattern(&grid, &pattern, Symmetry::Reflections);
+        let positions = mirrored
+            .iter()
+            .map(|pattern_match| pattern_match.top_left)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(0, 0), (1, 1)]);
+
+        let vertical = find_pattern(&grid, &Pattern::parse("S/X").unwrap(), Symmetry::Rotations);
+        assert_eq!(vertical.len(), 3);
+        assert!(find_pattern(&grid, &Pattern::parse("").unwrap(), Symmetry::All).is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut glyphs = Glyphs::new(Cells::Chars);
+        let grid = glyphs.grid(include_str!("input_test"), false).unwrap();
+        let xmas = find_word(&grid, glyphs.encode(XMAS));
+        let x_mas = Pattern::parse_with(X_MAS, Cells::Chars, |cell| Some(glyphs.glyph(cell)));
+        let x_mas = x_mas.unwrap();
+
+        let words = render_words(&grid, &glyphs, &xmas, Style::Plain);
+        let expected_words = "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+";
+        assert_eq!(words, expected_words);
+
+        let crosses = render_patterns(
+            &grid,
+            &glyphs,
+            &x_mas,
+            &second_part_matches(&grid, &x_mas),
+            Style::Plain,
+        );
+        let expected_crosses = "\
+.M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+";
+        assert_eq!(crosses, expected_crosses);
+
+        let grid = glyphs.grid("XMAS\nM..A", false).unwrap();
+        let xm = find_word(&grid, glyphs.encode("XM"));
+        let coloured = render_words(&grid, &glyphs, &xm, Style::Ansi);
+        // the X is shared by both matches and drawn in the colour of the later one
+        assert_eq!(
+            coloured,
+            "\x1b[35mX\x1b[0m\x1b[33mM\x1b[0m..\n\x1b[35mM\x1b[0m...\n"
+        );
     }
 
     #[test]
     fn test_second_part() {
         let input = include_str!("input_test");
-        
-        assert_eq!(second_part(input), 9);
+
+        assert_eq!(second_part(input), Ok(9));
+    }
+}
+
+/// Compares the searches on the original `Vec<Vec<char>>` grid, which copied
+/// every column, diagonal and window, with the borrowing `Grid` views:
+/// `cargo test --release --bin day04 bench_views -- --ignored --nocapture`.
+/// `DAY04_BENCH_SIZE` sets the side length of the random grid (default 10000).
+#[cfg(test)]
+mod bench {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// The grid functions as they were before `Grid`, kept for comparison.
+    trait LegacyGrid {
+        fn cols(&self) -> Vec<Vec<char>>;
+        fn diagonals(&self) -> Vec<Vec<char>>;
+    }
+
+    impl LegacyGrid for [Vec<char>] {
+        fn cols(&self) -> Vec<Vec<char>> {
+            (0..self[0].len())
+                .map(|col_idx| self.iter().map(|row| row[col_idx]).collect::<Vec<_>>())
+                .collect()
+        }
+
+        fn diagonals(&self) -> Vec<Vec<char>> {
+            let (rows, cols) = (self.len(), self[0].len());
+
+            let top_forward_diagonals = (0..cols).map(|col_idx| {
+                (0..rows)
+                    .filter(|row_idx| row_idx + col_idx < cols)
+                    .map(|row_idx| self[row_idx][row_idx + col_idx])
+                    .collect::<Vec<_>>()
+            });
+            let top_backward_diagonals = (0..cols).rev().map(|col_idx| {
+                (0..rows)
+                    .filter_map(|row_idx| Some(self[row_idx][col_idx.checked_sub(row_idx)?]))
+                    .collect::<Vec<_>>()
+            });
+            let side_forward_diagonals = (1..rows).map(|row_idx| {
+                (0..cols)
+                    .filter(|col_idx| row_idx + col_idx < rows)
+                    .map(|col_idx| self[row_idx + col_idx][col_idx])
+                    .collect::<Vec<_>>()
+            });
+            let side_backward_diagonals = (1..rows).map(|row_idx| {
+                (0..cols)
+                    .rev()
+                    .enumerate()
+                    .filter(|(step, _)| row_idx + step < rows)
+                    .map(|(step, col_idx)| self[row_idx + step][col_idx])
+                    .collect::<Vec<_>>()
+            });
+
+            top_forward_diagonals
+                .chain(top_backward_diagonals)
+                .chain(side_forward_diagonals)
+                .chain(side_backward_diagonals)
+                .collect()
+        }
+    }
+
+    fn legacy_hits(lines: &[Vec<char>], search: &str) -> usize {
+        let reversed = search.chars().rev().collect::<String>();
+
+        lines
+            .iter()
+            .map(|line| {
+                line.windows(search.len())
+                    .filter(|window| {
+                        let window = window.iter().collect::<String>();
+                        window == search || window == reversed
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    fn legacy_first_part(grid: &[Vec<char>]) -> usize {
+        legacy_hits(grid, "XMAS")
+            + legacy_hits(&grid.cols(), "XMAS")
+            + legacy_hits(&grid.diagonals(), "XMAS")
+    }
+
+    fn legacy_second_part(grid: &[Vec<char>]) -> usize {
+        let (rows, cols) = (grid.len(), grid[0].len());
+
+        (0..rows.saturating_sub(2))
+            .flat_map(|row| (0..cols.saturating_sub(2)).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                let window = (row..row + 3)
+                    .map(|row| grid[row][col..col + 3].to_vec())
+                    .collect::<Vec<_>>();
+
+                window
+                    .diagonals()
+                    .iter()
+                    .filter(|diag| diag.len() == 3)
+                    .all(|diag| {
+                        let diag = diag.iter().collect::<String>();
+                        diag == "MAS" || diag == "SAM"
+                    })
+            })
+            .count()
+    }
+
+    fn random_grid(size: usize) -> String {
+        let mut state = 0x2024_u64;
+        let mut text = String::with_capacity(size * (size + 1));
+
+        for _ in 0..size {
+            for _ in 0..size {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                text.push(['X', 'M', 'A', 'S'][(state >> 62) as usize]);
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+
+    fn timed<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+        let start = Instant::now();
+        let result = f();
+        (result, start.elapsed())
+    }
+
+    #[test]
+    #[ignore = "benchmark, run in release mode"]
+    fn bench_views() {
+        let size = std::env::var("DAY04_BENCH_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(10_000);
+        let text = random_grid(size);
+
+        let legacy = text
+            .lines()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let (legacy_words, legacy_words_time) = timed(|| legacy_first_part(&legacy));
+        let (legacy_xmas, legacy_xmas_time) = timed(|| legacy_second_part(&legacy));
+        drop(legacy);
+
+        let grid = build_grid(&text).unwrap();
+        let (found, find_word_time) = timed(|| find_word(&grid, "XMAS".chars()).len());
+        let xmas = ["XMAS".chars().collect::<Vec<_>>()];
+        let (counted, count_words_time) = timed(|| count_words(&grid, &xmas)[0]);
+        let pattern = Pattern::parse("M.S/.A./M.S").unwrap();
+        let (xmas, pattern_time) = timed(|| find_pattern(&grid, &pattern, Symmetry::All).len());
+
+        println!("{size}x{size} grid");
+        println!("XMAS   legacy Vec<Vec<char>>  {legacy_words:>10}  {legacy_words_time:>12.2?}");
+        println!("XMAS   find_word              {found:>10}  {find_word_time:>12.2?}");
+        println!("XMAS   count_words            {counted:>10}  {count_words_time:>12.2?}");
+        println!("X-MAS  legacy Vec<Vec<char>>  {legacy_xmas:>10}  {legacy_xmas_time:>12.2?}");
+        println!("X-MAS  find_pattern           {xmas:>10}  {pattern_time:>12.2?}");
+
+        assert_eq!(found, legacy_words);
+        assert_eq!(counted, legacy_words);
+        assert_eq!(xmas, legacy_xmas);
     }
 }
@@ -1,145 +1,1273 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
 
 fn main() {
     let input = include_str!("input");
-    println!("First part: {}", first_part(input));
-    println!("Second part: {}", second_part(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let options = match Options::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let text = match &options.file {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("{path}: {err}");
+                std::process::exit(1);
+            }
+        },
+        None => input.to_string(),
+    };
+
+    let (rules, updates) = match get_rules_and_updates(&text) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    // even lengths are up to the middle page policy
+    for issue in update_issues(&updates) {
+        if let UpdateIssue::DuplicatePage { .. } = issue {
+            eprintln!("warning: {issue}");
+        }
+    }
+
+    let mut queue = PrintQueue::new(rules, updates);
+    let list = |updates: &[usize]| {
+        updates
+            .iter()
+            .map(|idx| (idx + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    for edit in &options.edits {
+        match *edit {
+            Edit::Insert(rule) => match queue.insert(rule) {
+                Ok(changed) if changed.is_empty() => println!("inserted {rule}"),
+                Ok(changed) => println!("inserted {rule}, now invalid: {}", list(&changed)),
+                Err(err) => println!("rejected {rule}: {err}"),
+            },
+            Edit::Remove(rule) if !queue.rules().contains(rule) => println!("no rule {rule}"),
+            Edit::Remove(rule) => match queue.remove(rule) {
+                changed if changed.is_empty() => println!("removed {rule}"),
+                changed => println!("removed {rule}, now valid: {}", list(&changed)),
+            },
+        }
+    }
+
+    let (rules, updates) = (queue.rules(), queue.updates());
+
+    if options.dot {
+        let update = match options.update.map(|idx| updates.get(idx.wrapping_sub(1))) {
+            Some(Some(update)) => Some(update.as_slice()),
+            Some(None) => {
+                eprintln!("no update {}", options.update.unwrap_or_default());
+                std::process::exit(2);
+            }
+            None => None,
+        };
+
+        print!("{}", to_dot(rules, update));
+    }
+
+    if options.summary {
+        print!("{}", summary(rules));
+    }
+
+    if options.report {
+        print!("{}", report(rules, updates, options.repair));
+    }
+
+    if options.dot || options.summary || options.report {
+        return;
+    }
+
+    let parts = [
+        ("First", sum_valid(rules, updates, options.middle)),
+        ("Second", sum_fixed(rules, updates, options.middle)),
+    ];
+
+    for (part, sum) in parts {
+        match sum {
+            Ok(sum) => {
+                let action = match options.middle {
+                    MiddlePolicy::Lower => "took the lower middle page of",
+                    MiddlePolicy::Upper => "took the upper middle page of",
+                    _ => "skipped",
+                };
+
+                if !sum.even.is_empty() {
+                    eprintln!(
+                        "warning: {} part {action} updates {}",
+                        part.to_lowercase(),
+                        list(&sum.even)
+                    );
+                }
+
+                println!("{part} part: {}", sum.sum);
+            }
+            Err(err) => {
+                eprintln!("{part} part: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// A change to the rules given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Insert(Rule),
+    Remove(Rule),
+}
+
+/// Command line options for checking other rules and updates than the puzzle's.
+struct Options {
+    /// Read rules and updates from this file instead of the bundled input.
+    file: Option<String>,
+    /// List the invalid updates with the rules they break instead of the answers.
+    report: bool,
+    /// Repair the updates in the report moving as few pages as possible.
+    repair: bool,
+    /// Print the rule graph as Graphviz DOT instead of the answers.
+    dot: bool,
+    /// Only draw the rules between the pages of this update (1-based).
+    update: Option<usize>,
+    /// Describe the structure of the rules instead of the answers.
+    summary: bool,
+    /// Changes to the rules, applied in order before anything else.
+    edits: Vec<Edit>,
+    middle: MiddlePolicy,
+}
+
+impl Options {
+    /// Parses `--file <path>`, `--report`, `--repair` (a report with minimal
+    /// repairs), `--dot`, `--update <n>` and `--summary`, as well as
+    /// `--insert <a|b>` and `--remove <a|b>` to change the rules first and
+    /// `--middle <error|lower|upper|skip>` for updates with an even number of pages.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            file: None,
+            report: false,
+            repair: false,
+            dot: false,
+            update: None,
+            summary: false,
+            edits: Vec::new(),
+            middle: MiddlePolicy::default(),
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--file" => {
+                    let path = args.next().ok_or(format!("missing value for {arg}"))?;
+                    options.file = Some(path.clone());
+                }
+                "--report" => options.report = true,
+                "--repair" => {
+                    options.report = true;
+                    options.repair = true;
+                }
+                "--dot" => options.dot = true,
+                "--update" => {
+                    let idx = args.next().ok_or(format!("missing value for {arg}"))?;
+                    let idx = idx
+                        .parse()
+                        .map_err(|_| format!("invalid update number: {idx}"))?;
+                    options.update = Some(idx);
+                }
+                "--summary" => options.summary = true,
+                "--middle" => {
+                    options.middle = match args.next().map(String::as_str) {
+                        Some("error") => MiddlePolicy::Error,
+                        Some("lower") => MiddlePolicy::Lower,
+                        Some("upper") => MiddlePolicy::Upper,
+                        Some("skip") => MiddlePolicy::Skip,
+                        Some(other) => return Err(format!("invalid middle page policy: {other}")),
+                        None => return Err(format!("missing value for {arg}")),
+                    }
+                }
+                "--insert" | "--remove" => {
+                    let rule = args.next().ok_or(format!("missing value for {arg}"))?;
+                    let rule = Rule::parse(rule).ok_or(format!("invalid rule: {rule}"))?;
+
+                    options.edits.push(if arg == "--insert" {
+                        Edit::Insert(rule)
+                    } else {
+                        Edit::Remove(rule)
+                    });
+                }
+                other => return Err(format!("unknown argument: {other}")),
+            }
+        }
+
+        if options.update.is_some() && !options.dot {
+            return Err("--update only works with --dot".to_string());
+        }
+
+        Ok(options)
+    }
 }
 
-#[derive(Clone)]
+/// The first page has to be printed before the second one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Rule(u64, u64);
 
-fn check_valid_update(update: &Vec<u64>, rules: &Vec<Rule>) -> bool {
-    let index_list = update
-        .iter()
-        .enumerate()
-        .map(|(idx, val)| (*val, idx))
-        .collect::<HashMap<u64, usize>>();
+/// Every rule indexed by page, both ways.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    /// The pages that have to be printed after a page.
+    after: HashMap<u64, HashSet<u64>>,
+    /// The pages that have to be printed before a page.
+    before: HashMap<u64, HashSet<u64>>,
+}
+
+impl RuleSet {
+    /// Adds a rule, returning whether it's new.
+    fn insert(&mut self, rule: Rule) -> bool {
+        self.before.entry(rule.1).or_default().insert(rule.0);
+        self.after.entry(rule.0).or_default().insert(rule.1)
+    }
+
+    /// Drops a rule, returning whether it was there.
+    fn remove(&mut self, rule: Rule) -> bool {
+        let drop = |pages: &mut HashMap<u64, HashSet<u64>>, page: u64, other: u64| {
+            let Some(others) = pages.get_mut(&page) else {
+                return false;
+            };
+            let removed = others.remove(&other);
+
+            if others.is_empty() {
+                pages.remove(&page);
+            }
+
+            removed
+        };
+
+        drop(&mut self.before, rule.1, rule.0);
+        drop(&mut self.after, rule.0, rule.1)
+    }
+
+    fn contains(&self, rule: Rule) -> bool {
+        self.after
+            .get(&rule.0)
+            .is_some_and(|after| after.contains(&rule.1))
+    }
+
+    fn after(&self, page: u64) -> impl Iterator<Item = u64> + '_ {
+        self.after.get(&page).into_iter().flatten().copied()
+    }
+
+    fn before(&self, page: u64) -> impl Iterator<Item = u64> + '_ {
+        self.before.get(&page).into_iter().flatten().copied()
+    }
+
+    /// Every page named in a rule, in ascending order.
+    fn pages(&self) -> Vec<u64> {
+        let mut pages = self
+            .after
+            .keys()
+            .chain(self.before.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        pages.sort_unstable();
+        pages.dedup();
+        pages
+    }
+
+    /// Every rule, ordered by the first and then the second page.
+    fn rules(&self) -> Vec<Rule> {
+        let mut rules = self
+            .after
+            .iter()
+            .flat_map(|(first, after)| after.iter().map(|second| Rule(*first, *second)))
+            .collect::<Vec<_>>();
+        rules.sort_unstable_by_key(|rule| (rule.0, rule.1));
+        rules
+    }
+
+    /// The fewest rules implying the same order of pages, i.e. without any
+    /// rule that follows from others. Only unique if the rules have no cycle.
+    fn transitive_reduction(&self) -> Result<RuleSet, CycleError> {
+        let order = fix_invalid_update(&self.pages(), self)?;
+        let position = order
+            .iter()
+            .enumerate()
+            .map(|(idx, page)| (*page, idx))
+            .collect::<HashMap<_, _>>();
+
+        // pages reachable from every page, as bit sets over positions in `order`
+        let words = order.len().div_ceil(64);
+        let mut reachable = vec![vec![0u64; words]; order.len()];
+        let mut reduction = RuleSet::default();
+
+        for (idx, page) in order.iter().enumerate().rev() {
+            let mut successors = self
+                .after(*page)
+                .map(|next| position[&next])
+                .collect::<Vec<_>>();
+            successors.sort_unstable();
+
+            // closer successors come first, so any other path to a successor
+            // goes through one already seen
+            for next in successors {
+                if reachable[idx][next / 64] & (1 << (next % 64)) != 0 {
+                    continue;
+                }
 
-    rules.iter().all(|rule| {
-        let first_idx = index_list.get(&rule.0);
-        let second_idx = index_list.get(&rule.1);
+                reduction.insert(Rule(*page, order[next]));
+                reachable[idx][next / 64] |= 1 << (next % 64);
 
-        match (first_idx, second_idx) {
-            (Some(first), Some(second)) => first < second,
-            _ => true,
+                // successors come later in `order`
+                let (head, tail) = reachable.split_at_mut(next);
+
+                for (word, bits) in head[idx].iter_mut().zip(&tail[0]) {
+                    *word |= bits;
+                }
+            }
         }
-    })
+
+        Ok(reduction)
+    }
 }
 
-fn fix_invalid_update(update: &Vec<u64>, rules: &Vec<Rule>) -> Vec<u64> {
-    let mut root_nums = update
-        .iter()
-        .filter(|num| {
-            !rules.iter().any(|rule| &rule.1 == *num)
-                || rules
-                    .iter()
-                    .filter(|rule| (rule.1 == **num))
-                    .all(|rule| !update.contains(&rule.0))
-        })
-        .map(|num| *num)
-        .collect::<Vec<_>>();
+impl FromIterator<Rule> for RuleSet {
+    fn from_iter<I: IntoIterator<Item = Rule>>(iter: I) -> Self {
+        let mut rules = RuleSet::default();
 
-    let mut bound_nums = update
-        .iter()
-        .filter(|num| rules.iter().any(|rule| &rule.1 == *num))
-        .map(|num| *num)
-        .collect::<Vec<_>>();
+        for rule in iter {
+            rules.insert(rule);
+        }
+
+        rules
+    }
+}
+
+impl Rule {
+    /// Parses `first|second`.
+    fn parse(text: &str) -> Option<Rule> {
+        let (first, second) = text.split_once('|')?;
+        let first = first.trim().parse::<u64>().ok()?;
+        let second = second.trim().parse::<u64>().ok()?;
+
+        Some(Rule(first, second))
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.0, self.1)
+    }
+}
+
+/// A rule broken by an update, with the indices of its pages in the update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Violation {
+    rule: Rule,
+    /// Where the page to be printed first is, behind `second`.
+    first: usize,
+    second: usize,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at index {} comes after {} at index {}, breaking {}",
+            self.rule.0, self.first, self.rule.1, self.second, self.rule
+        )
+    }
+}
+
+/// Every rule the update breaks, ordered by the indices of the pages. A page
+/// listed twice is checked against the earliest index of the other page.
+fn violations(update: &[u64], rules: &RuleSet) -> Vec<Violation> {
+    let mut seen = HashMap::with_capacity(update.len());
+    let mut violations = Vec::new();
+
+    for (idx, page) in update.iter().enumerate() {
+        for later in rules.after(*page) {
+            if let Some(&second) = seen.get(&later) {
+                violations.push(Violation {
+                    rule: Rule(*page, later),
+                    first: idx,
+                    second,
+                });
+            }
+        }
+
+        seen.entry(*page).or_insert(idx);
+    }
+
+    violations.sort_by_key(|violation| (violation.first, violation.second));
+    violations
+}
+
+/// Lists every invalid update with the rules it breaks and the order the
+/// fixer puts it in, or with `repair` the one moving the fewest pages.
+fn report(rules: &RuleSet, updates: &[Vec<u64>], repair: bool) -> String {
+    let join = |pages: &[u64]| {
+        pages
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut out = String::new();
+    let mut invalid = 0;
+
+    for (idx, update) in updates.iter().enumerate() {
+        let violations = violations(update, rules);
+
+        if violations.is_empty() {
+            continue;
+        }
 
-    let mut idx = 0;
+        invalid += 1;
+        out += &format!("update {}: {}\n", idx + 1, join(update));
 
-    loop {
-        if idx >= bound_nums.len() {
-            idx = 0;
+        for violation in violations {
+            out += &format!("  {violation}\n");
+        }
+
+        if repair {
+            match repair_update(update, rules) {
+                Ok(repair) => {
+                    out += &format!("  repaired: {}\n", join(&repair.order));
+                    out += &format!("  moved: {}\n", join(&repair.moved));
+                }
+                Err(err) => out += &format!("  not repairable: {err}\n"),
+            }
+        } else {
+            match fix_invalid_update(update, rules) {
+                Ok(fixed) => out += &format!("  fixed: {}\n", join(&fixed)),
+                Err(err) => out += &format!("  not fixable: {err}\n"),
+            }
+        }
+    }
+
+    out += &format!("{} updates, {invalid} invalid\n", updates.len());
+    out
+}
+
+/// Writes the rule graph as Graphviz DOT, or only the rules between the pages of
+/// `update` with the ones it breaks in red.
+fn to_dot(rules: &RuleSet, update: Option<&[u64]>) -> String {
+    let (pages, broken) = match update {
+        Some(update) => {
+            let mut pages = update.to_vec();
+            pages.sort_unstable();
+            pages.dedup();
+
+            let broken = violations(update, rules)
+                .into_iter()
+                .map(|violation| violation.rule)
+                .collect::<Vec<_>>();
+
+            (pages, broken)
+        }
+        None => (rules.pages(), Vec::new()),
+    };
+
+    let mut out = String::from("digraph rules {\n");
+
+    for page in &pages {
+        out += &format!("    {page};\n");
+    }
+
+    for rule in rules.rules() {
+        if pages.binary_search(&rule.0).is_err() || pages.binary_search(&rule.1).is_err() {
             continue;
         }
 
-        let Some(next) = bound_nums.get(idx) else {
-            break;
+        let style = if broken.contains(&rule) {
+            " [color=red]"
+        } else {
+            ""
         };
+        out += &format!("    {} -> {}{style};\n", rule.0, rule.1);
+    }
+
+    out += "}\n";
+    out
+}
+
+/// Describes the rule graph: its size, the size of its transitive reduction
+/// and whether it puts all pages into a single order.
+fn summary(rules: &RuleSet) -> String {
+    let pages = rules.pages();
+    let mut out = format!("{} pages, {} rules\n", pages.len(), rules.rules().len());
+
+    match rules.transitive_reduction() {
+        Ok(reduction) => {
+            // a single order is a chain, so sorted every page has to be
+            // followed by one the reduction puts right after it
+            let graph = UpdateGraph::new(&pages, &reduction);
+            let total = graph.order().is_ok_and(|order| {
+                order.windows(2).all(|pair| {
+                    reduction.contains(Rule(graph.pages[pair[0]], graph.pages[pair[1]]))
+                })
+            });
+
+            out += &format!("transitive reduction: {} rules\n", reduction.rules().len());
+            out += &format!("total order: {}\n", if total { "yes" } else { "no" });
+        }
+        Err(err) => {
+            out += &format!("no transitive reduction, {err}\n");
+            out += "total order: no\n";
+        }
+    }
+
+    out
+}
+
+/// Whether no page is printed after one that the rules want after it.
+fn check_valid_update(update: &[u64], rules: &RuleSet) -> bool {
+    let mut seen = HashSet::with_capacity(update.len());
+
+    update.iter().all(|page| {
+        let valid = rules.after(*page).all(|later| !seen.contains(&later));
+        seen.insert(*page);
+        valid
+    })
+}
+
+/// The pages on a cycle of rules, each one to be printed before the next and
+/// the last one before the first, so no order satisfies all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pages: Vec<u64>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pages = self
+            .pages
+            .iter()
+            .chain(self.pages.first())
+            .map(u64::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "rules form a cycle: {}", pages.join(" -> "))
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// The rules between the pages of an update, with pages numbered by their
+/// first index in the update.
+struct UpdateGraph {
+    pages: Vec<u64>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl UpdateGraph {
+    fn new(update: &[u64], rules: &RuleSet) -> Self {
+        let mut pages = Vec::new();
+        let mut index = HashMap::new();
+
+        for page in update {
+            index.entry(*page).or_insert_with(|| {
+                pages.push(*page);
+                pages.len() - 1
+            });
+        }
 
-        let nums_before = rules
+        let successors = pages
             .iter()
-            .filter(|rule| rule.1 == *next && update.contains(&rule.0))
-            .map(|rule| rule.0)
+            .map(|page| {
+                rules
+                    .after(*page)
+                    .filter_map(|next| index.get(&next).copied())
+                    .collect::<Vec<_>>()
+            })
             .collect::<Vec<_>>();
+        let predecessors = pages
+            .iter()
+            .map(|page| {
+                rules
+                    .before(*page)
+                    .filter_map(|prev| index.get(&prev).copied())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            pages,
+            successors,
+            predecessors,
+        }
+    }
+
+    fn add_edge(&mut self, first: usize, second: usize) {
+        self.successors[first].push(second);
+        self.predecessors[second].push(first);
+    }
+
+    /// Sorts the pages topologically with Kahn's algorithm, taking pages free
+    /// to go next in the order of the update.
+    fn order(&self) -> Result<Vec<usize>, CycleError> {
+        let mut in_degree = self.predecessors.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = (0..self.pages.len())
+            .filter(|page| in_degree[*page] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut ordered = Vec::with_capacity(self.pages.len());
+
+        while let Some(Reverse(page)) = ready.pop() {
+            ordered.push(page);
+
+            for next in &self.successors[page] {
+                in_degree[*next] -= 1;
+
+                if in_degree[*next] == 0 {
+                    ready.push(Reverse(*next));
+                }
+            }
+        }
+
+        if ordered.len() == self.pages.len() {
+            return Ok(ordered);
+        }
 
-        if !nums_before.iter().all(|num| root_nums.contains(num)) {
-            idx += 1;
+        // every page left has a predecessor that is left as well, so walking
+        // backwards from any of them has to run into a cycle
+        let mut page = (0..self.pages.len())
+            .find(|page| in_degree[*page] > 0)
+            .expect("a page left over");
+        let mut path = Vec::new();
+
+        while !path.contains(&page) {
+            path.push(page);
+            page = *self.predecessors[page]
+                .iter()
+                .filter(|prev| in_degree[**prev] > 0)
+                .min()
+                .expect("a predecessor left over");
+        }
+
+        let start = path
+            .iter()
+            .position(|prev| *prev == page)
+            .expect("on the path");
+        let pages = path[start..]
+            .iter()
+            .rev()
+            .map(|page| self.pages[*page])
+            .collect();
+
+        Err(CycleError { pages })
+    }
+
+    /// For every page, whether the rules put each other page somewhere after it,
+    /// given the pages in topological `order`.
+    fn reachable(&self, order: &[usize]) -> Vec<Vec<bool>> {
+        let mut reachable = vec![vec![false; self.pages.len()]; self.pages.len()];
+
+        for page in order.iter().rev() {
+            for next in &self.successors[*page] {
+                let later = reachable[*next].clone();
+
+                reachable[*page][*next] = true;
+
+                for (reached, later) in reachable[*page].iter_mut().zip(later) {
+                    *reached |= later;
+                }
+            }
+        }
+
+        reachable
+    }
+}
+
+/// Orders the pages of an update by the rules between them with Kahn's
+/// algorithm. Pages free to go next are taken in the order of the update,
+/// so a valid update stays as it is. Pages listed twice only appear once.
+fn fix_invalid_update(update: &[u64], rules: &RuleSet) -> Result<Vec<u64>, CycleError> {
+    let graph = UpdateGraph::new(update, rules);
+    let order = graph.order()?;
+
+    Ok(order.into_iter().map(|page| graph.pages[page]).collect())
+}
+
+/// A valid order for an update that moves as few pages as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Repair {
+    order: Vec<u64>,
+    /// The pages not kept in their order, as they appear in the update.
+    moved: Vec<u64>,
+}
+
+/// Orders an update keeping the longest subsequence of its pages in place
+/// that no rule, even through other pages, wants in a different order, and
+/// moves only the others. Pages listed twice only appear once.
+fn repair_update(update: &[u64], rules: &RuleSet) -> Result<Repair, CycleError> {
+    let mut graph = UpdateGraph::new(update, rules);
+    let reachable = graph.reachable(&graph.order()?);
+    let len = graph.pages.len();
+
+    // Two pages can't both stay if the rules want the later one first. Those
+    // pairs are ordered by both the rules and the reversed update, so this is
+    // a partial order, and the pages to keep are a largest antichain. By
+    // Dilworth and König it's found through a maximum matching between the
+    // pages as inverted pairs' later and earlier ones.
+    let inverted = |later: usize, earlier: usize| earlier < later && reachable[later][earlier];
+
+    let mut matched_earlier = vec![None; len];
+    let mut matched_later = vec![None; len];
+
+    for later in 0..len {
+        let mut seen = vec![false; len];
+        augment(later, &inverted, &mut matched_earlier, &mut seen);
+    }
+
+    for (earlier, later) in matched_earlier.iter().enumerate() {
+        if let Some(later) = later {
+            matched_later[*later] = Some(earlier);
+        }
+    }
+
+    // pages reachable from an unmatched page on the left by alternating paths
+    let mut left = vec![false; len];
+    let mut right = vec![false; len];
+    let mut queue = (0..len)
+        .filter(|later| matched_later[*later].is_none())
+        .collect::<VecDeque<_>>();
+
+    for later in &queue {
+        left[*later] = true;
+    }
+
+    while let Some(later) = queue.pop_front() {
+        for earlier in (0..len).filter(|earlier| inverted(later, *earlier)) {
+            if right[earlier] {
+                continue;
+            }
+
+            right[earlier] = true;
+
+            if let Some(next) = matched_earlier[earlier].filter(|next| !left[*next]) {
+                left[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let kept = (0..len)
+        .map(|page| left[page] && !right[page])
+        .collect::<Vec<_>>();
+    let kept_pages = (0..len).filter(|page| kept[*page]).collect::<Vec<_>>();
+
+    for pair in kept_pages.windows(2) {
+        graph.add_edge(pair[0], pair[1]);
+    }
+
+    let order = graph
+        .order()
+        .expect("the kept pages agree with the rules")
+        .into_iter()
+        .map(|page| graph.pages[page])
+        .collect();
+    let moved = (0..len)
+        .filter(|page| !kept[*page])
+        .map(|page| graph.pages[page])
+        .collect();
+
+    Ok(Repair { order, moved })
+}
+
+/// Looks for an augmenting path from `later` in Kuhn's matching algorithm.
+fn augment(
+    later: usize,
+    inverted: &impl Fn(usize, usize) -> bool,
+    matched_earlier: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for earlier in 0..matched_earlier.len() {
+        if !inverted(later, earlier) || seen[earlier] {
             continue;
         }
 
-        if !root_nums.contains(next) {
-            root_nums.push(*next);
+        seen[earlier] = true;
+
+        let free = match matched_earlier[earlier] {
+            None => true,
+            Some(other) => augment(other, inverted, matched_earlier, seen),
+        };
+
+        if free {
+            matched_earlier[earlier] = Some(later);
+            return true;
         }
+    }
+
+    false
+}
 
-        bound_nums.remove(idx);
+/// Rules together with the updates checked against them, which are
+/// re-validated as rules are inserted and removed.
+#[derive(Debug, Clone)]
+struct PrintQueue {
+    rules: RuleSet,
+    updates: Vec<Vec<u64>>,
+    valid: Vec<bool>,
+    /// Indices of the updates each page is in, ascending.
+    containing: HashMap<u64, Vec<usize>>,
+}
+
+impl PrintQueue {
+    fn new(rules: RuleSet, updates: Vec<Vec<u64>>) -> Self {
+        let valid = updates
+            .iter()
+            .map(|update| check_valid_update(update, &rules))
+            .collect();
+        let mut containing = HashMap::<u64, Vec<usize>>::new();
+
+        for (idx, update) in updates.iter().enumerate() {
+            for page in update {
+                let updates = containing.entry(*page).or_default();
+
+                if updates.last() != Some(&idx) {
+                    updates.push(idx);
+                }
+            }
+        }
 
-        if bound_nums.is_empty() {
-            break;
+        Self {
+            rules,
+            updates,
+            valid,
+            containing,
         }
     }
 
-    root_nums
+    fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    fn updates(&self) -> &[Vec<u64>] {
+        &self.updates
+    }
+
+    /// Indices of the updates with both pages of `rule`, the only ones it applies to.
+    fn affected(&self, rule: Rule) -> Vec<usize> {
+        let (Some(first), Some(second)) =
+            (self.containing.get(&rule.0), self.containing.get(&rule.1))
+        else {
+            return Vec::new();
+        };
+
+        first
+            .iter()
+            .copied()
+            .filter(|idx| second.binary_search(idx).is_ok())
+            .collect()
+    }
+
+    /// Adds a rule unless it closes a cycle among the pages of an update, which
+    /// then couldn't be ordered anymore. Returns the indices of the updates
+    /// that were valid before, but aren't anymore.
+    fn insert(&mut self, rule: Rule) -> Result<Vec<usize>, CycleError> {
+        if self.rules.contains(rule) {
+            return Ok(Vec::new());
+        }
+
+        let affected = self.affected(rule);
+
+        for idx in &affected {
+            if let Some(path) = self.path(*idx, rule.1, rule.0) {
+                // the path leads from the second page back to the first
+                let pages = [rule.0]
+                    .into_iter()
+                    .chain(path[..path.len() - 1].iter().copied())
+                    .collect();
+
+                return Err(CycleError { pages });
+            }
+        }
+
+        self.rules.insert(rule);
+        Ok(self.revalidate(&affected))
+    }
+
+    /// Drops a rule, returning the indices of the updates that are valid now,
+    /// but weren't before.
+    fn remove(&mut self, rule: Rule) -> Vec<usize> {
+        if !self.rules.remove(rule) {
+            return Vec::new();
+        }
+
+        let affected = self.affected(rule);
+        self.revalidate(&affected)
+    }
+
+    /// The pages on a chain of rules from `from` to `to` within the pages of an
+    /// update, including both ends, if any.
+    fn path(&self, idx: usize, from: u64, to: u64) -> Option<Vec<u64>> {
+        let pages = self.updates[idx].iter().copied().collect::<HashSet<_>>();
+        let mut previous = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(page) = queue.pop_front() {
+            if page == to {
+                let mut path = vec![to];
+                let mut curr = to;
+
+                while curr != from {
+                    curr = previous[&curr];
+                    path.push(curr);
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            let mut next = self
+                .rules
+                .after(page)
+                .filter(|next| pages.contains(next) && !previous.contains_key(next))
+                .collect::<Vec<_>>();
+            next.sort_unstable();
+
+            for next in next {
+                previous.insert(next, page);
+                queue.push_back(next);
+            }
+        }
+
+        None
+    }
+
+    /// Checks the given updates again, returning those whose validity changed.
+    fn revalidate(&mut self, updates: &[usize]) -> Vec<usize> {
+        updates
+            .iter()
+            .copied()
+            .filter(|idx| {
+                let valid = check_valid_update(&self.updates[*idx], &self.rules);
+                let changed = valid != self.valid[*idx];
+
+                self.valid[*idx] = valid;
+                changed
+            })
+            .collect()
+    }
 }
 
-fn get_rules_and_updates(input: &str) -> (Vec<Rule>, Vec<Vec<u64>>) {
-    let mut parts = input.split("\n\n");
-    let rules = parts.next().expect("malformed input");
-    let updates = parts.next().expect("malformed input");
+/// Why the rules and updates couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    MissingRules,
+    MissingUpdates,
+    /// A section after the updates, starting at the given line (1-based).
+    ExtraSection(usize),
+    InvalidRule {
+        line: usize,
+        text: String,
+    },
+    InvalidUpdate {
+        line: usize,
+        text: String,
+    },
+}
 
-    let rules = rules
-        .lines()
-        .flat_map(|line| {
-            let mut parts = line.split('|');
-            let first = parts.next()?.parse::<u64>().ok()?;
-            let second = parts.next()?.parse::<u64>().ok()?;
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::MissingRules => write!(f, "no rules before the updates"),
+            InputError::MissingUpdates => write!(f, "no updates after the rules"),
+            InputError::ExtraSection(line) => {
+                write!(f, "line {line}: unexpected section after the updates")
+            }
+            InputError::InvalidRule { line, text } => {
+                write!(f, "line {line}: expected a rule like 47|53, found {text:?}")
+            }
+            InputError::InvalidUpdate { line, text } => {
+                write!(
+                    f,
+                    "line {line}: expected pages like 75,47,61, found {text:?}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
 
-            Some(Rule(first, second))
+/// Reads the rules and the updates, two sections separated by blank lines.
+/// Lines may end in CRLF and have whitespace around them.
+pub fn get_rules_and_updates(input: &str) -> Result<(RuleSet, Vec<Vec<u64>>), InputError> {
+    // runs of non-blank lines with the line number (1-based) of each
+    let mut sections: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut blank = true;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+
+        match (line.is_empty(), blank) {
+            (true, _) => {}
+            (false, true) => sections.push(vec![(idx + 1, line)]),
+            (false, false) => sections
+                .last_mut()
+                .expect("a section")
+                .push((idx + 1, line)),
+        }
+
+        blank = line.is_empty();
+    }
+
+    let (rules, updates) = match sections.as_slice() {
+        [] => return Err(InputError::MissingRules),
+        [only] if only[0].1.contains('|') => return Err(InputError::MissingUpdates),
+        [_] => return Err(InputError::MissingRules),
+        [rules, updates] => (rules, updates),
+        [_, _, extra, ..] => return Err(InputError::ExtraSection(extra[0].0)),
+    };
+
+    let rules = rules
+        .iter()
+        .map(|(line, text)| {
+            Rule::parse(text).ok_or_else(|| InputError::InvalidRule {
+                line: *line,
+                text: text.to_string(),
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<RuleSet, _>>()?;
 
     let updates = updates
-        .lines()
-        .map(|line| {
-            line.split(',')
-                .flat_map(|num| num.parse::<u64>())
-                .collect::<Vec<_>>()
+        .iter()
+        .map(|(line, text)| {
+            text.split(',')
+                .map(|page| page.trim().parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| InputError::InvalidUpdate {
+                    line: *line,
+                    text: text.to_string(),
+                })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, _>>()?;
 
-    (rules, updates)
+    Ok((rules, updates))
 }
 
-fn first_part(input: &str) -> u64 {
-    let (rules, updates) = get_rules_and_updates(input);
+/// Something about an update that leaves it without a well-defined middle page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UpdateIssue {
+    /// The update (0-based) lists a page more than once.
+    DuplicatePage { update: usize, page: u64 },
+    /// The update (0-based) has an even number of pages.
+    EvenLength { update: usize, len: usize },
+}
 
-    updates
-        .iter()
-        .filter(|update| check_valid_update(update, &rules))
-        .map(|update| {
-            let mid = update.len() / 2;
-            update[mid]
-        })
-        .sum()
+impl Display for UpdateIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateIssue::DuplicatePage { update, page } => {
+                write!(f, "update {} lists page {page} more than once", update + 1)
+            }
+            UpdateIssue::EvenLength { update, len } => write!(
+                f,
+                "update {} has {len} pages and no single middle one",
+                update + 1
+            ),
+        }
+    }
 }
 
-fn second_part(input: &str) -> u64 {
-    let (rules, updates) = get_rules_and_updates(input);
+/// Every update with duplicate pages or an even number of them.
+fn update_issues(updates: &[Vec<u64>]) -> Vec<UpdateIssue> {
+    let mut issues = Vec::new();
 
-    updates
-        .iter()
-        .filter(|update| !check_valid_update(update, &rules))
-        .map(|update| fix_invalid_update(update, &rules))
-        .map(|update| {
-            if !update.is_empty() {
-                let mid = update.len() / 2;
-                update[mid]
-            } else {
-                0
+    for (idx, update) in updates.iter().enumerate() {
+        let mut seen = HashSet::new();
+
+        if let Some(page) = update.iter().find(|page| !seen.insert(**page)) {
+            issues.push(UpdateIssue::DuplicatePage {
+                update: idx,
+                page: *page,
+            });
+        }
+
+        if update.len() % 2 == 0 {
+            issues.push(UpdateIssue::EvenLength {
+                update: idx,
+                len: update.len(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Which page counts as the middle of an update with an even number of pages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MiddlePolicy {
+    /// Refuse to sum such updates.
+    #[default]
+    Error,
+    /// The last page of the first half.
+    Lower,
+    /// The first page of the second half.
+    Upper,
+    /// Leave such updates out of the sum.
+    Skip,
+}
+
+impl MiddlePolicy {
+    /// The middle page of `update` by this policy, `Some(None)` if the update
+    /// is skipped and `None` if the policy refuses it.
+    fn middle(self, update: &[u64]) -> Option<Option<u64>> {
+        let mid = update.len() / 2;
+
+        match (update.len() % 2 == 1, self) {
+            (true, _) => Some(Some(update[mid])),
+            (false, MiddlePolicy::Skip) => Some(None),
+            (false, MiddlePolicy::Lower) if mid > 0 => Some(Some(update[mid - 1])),
+            (false, MiddlePolicy::Upper) if mid > 0 => Some(Some(update[mid])),
+            (false, _) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiddleError {
+    /// Updates without a single middle page, with the policy saying to refuse
+    /// them, by their indices (0-based).
+    NoMiddle { updates: Vec<usize> },
+    /// An invalid update (0-based) that can't be fixed.
+    Cycle { update: usize, err: CycleError },
+}
+
+impl Display for MiddleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MiddleError::NoMiddle { updates } => {
+                let updates = updates
+                    .iter()
+                    .map(|idx| (idx + 1).to_string())
+                    .collect::<Vec<_>>();
+
+                write!(
+                    f,
+                    "no single middle page in updates {}, choose one with a middle page policy",
+                    updates.join(", ")
+                )
             }
+            MiddleError::Cycle { update, err } => {
+                write!(f, "update {} is not fixable, {err}", update + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MiddleError {}
+
+/// A sum of middle pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiddleSum {
+    pub sum: u64,
+    /// Indices (0-based) of the updates with an even number of pages, whose
+    /// middle page the policy picked or which it skipped.
+    pub even: Vec<usize>,
+}
+
+/// Sums the middle page of every update by `policy`, failing with all
+/// updates without one if the policy doesn't allow for that.
+fn sum_middles<'u>(
+    updates: impl Iterator<Item = (usize, Cow<'u, [u64]>)>,
+    policy: MiddlePolicy,
+) -> Result<MiddleSum, MiddleError> {
+    let mut sum = 0;
+    let mut even = Vec::new();
+    let mut failed = Vec::new();
+
+    for (idx, update) in updates {
+        if update.len() % 2 == 0 {
+            even.push(idx);
+        }
+
+        match policy.middle(&update) {
+            Some(middle) => sum += middle.unwrap_or_default(),
+            None => failed.push(idx),
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(MiddleSum { sum, even })
+    } else {
+        Err(MiddleError::NoMiddle { updates: failed })
+    }
+}
+
+/// Sums the middle pages of the valid updates.
+fn sum_valid(
+    rules: &RuleSet,
+    updates: &[Vec<u64>],
+    policy: MiddlePolicy,
+) -> Result<MiddleSum, MiddleError> {
+    let valid = updates
+        .iter()
+        .enumerate()
+        .filter(|(_, update)| check_valid_update(update, rules))
+        .map(|(idx, update)| (idx, Cow::Borrowed(update.as_slice())));
+
+    sum_middles(valid, policy)
+}
+
+/// Sums the middle pages of the invalid updates once fixed, which drops
+/// pages listed twice. Fails on the first update the rules order in a cycle.
+fn sum_fixed(
+    rules: &RuleSet,
+    updates: &[Vec<u64>],
+    policy: MiddlePolicy,
+) -> Result<MiddleSum, MiddleError> {
+    let fixed = updates
+        .iter()
+        .enumerate()
+        .filter(|(_, update)| !check_valid_update(update, rules))
+        .map(|(idx, update)| match fix_invalid_update(update, rules) {
+            Ok(fixed) => Ok((idx, Cow::Owned(fixed))),
+            Err(err) => Err(MiddleError::Cycle { update: idx, err }),
         })
-        .sum()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    sum_middles(fixed.into_iter(), policy)
+}
+
+/// Sums the middle pages of the valid updates, picked by `policy`.
+pub fn first_part_with(input: &str, policy: MiddlePolicy) -> Result<MiddleSum, MiddleError> {
+    let (rules, updates) = get_rules_and_updates(input).expect("malformed input");
+
+    sum_valid(&rules, &updates, policy)
+}
+
+/// Sums the middle pages of the invalid updates once fixed, picked by `policy`.
+pub fn second_part_with(input: &str, policy: MiddlePolicy) -> Result<MiddleSum, MiddleError> {
+    let (rules, updates) = get_rules_and_updates(input).expect("malformed input");
+
+    sum_fixed(&rules, &updates, policy)
+}
+
+pub fn first_part(input: &str) -> Result<u64, MiddleError> {
+    first_part_with(input, MiddlePolicy::Error).map(|sum| sum.sum)
+}
+
+pub fn second_part(input: &str) -> Result<u64, MiddleError> {
+    second_part_with(input, MiddlePolicy::Error).map(|sum| sum.sum)
 }
 
 #[cfg(test)]
@@ -150,7 +1278,7 @@ mod tests {
     fn test_valid_updates() {
         let input = include_str!("input_test");
 
-        let (rules, updates) = get_rules_and_updates(input);
+        let (rules, updates) = get_rules_and_updates(input).unwrap();
 
         let count_valid = updates
             .iter()
@@ -160,15 +1288,558 @@ mod tests {
         assert_eq!(count_valid, 3);
     }
 
+    #[test]
+    fn test_fix_invalid_update() {
+        let input = include_str!("input_test");
+        let (rules, _) = get_rules_and_updates(input).unwrap();
+
+        assert_eq!(
+            fix_invalid_update(&[97, 13, 75, 29, 47], &rules),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+        assert_eq!(
+            fix_invalid_update(&[75, 47, 61, 53, 29], &rules),
+            Ok(vec![75, 47, 61, 53, 29])
+        );
+
+        // without rules between them, pages keep their order
+        assert_eq!(
+            fix_invalid_update(&[3, 1, 2, 1], &RuleSet::from_iter([Rule(2, 3)])),
+            Ok(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_fix_invalid_update_cycle() {
+        let rules =
+            RuleSet::from_iter([Rule(1, 2), Rule(2, 3), Rule(3, 1), Rule(4, 1), Rule(5, 6)]);
+        let err = fix_invalid_update(&[5, 4, 3, 2, 1], &rules).unwrap_err();
+
+        assert_eq!(err.pages, vec![1, 2, 3]);
+        assert_eq!(err.to_string(), "rules form a cycle: 1 -> 2 -> 3 -> 1");
+
+        // rules with pages outside the update don't matter
+        assert!(fix_invalid_update(&[5, 4, 3, 2], &rules).is_ok());
+        assert_eq!(
+            fix_invalid_update(&[7], &RuleSet::from_iter([Rule(7, 7)])),
+            Err(CycleError { pages: vec![7] })
+        );
+
+        let updates = [vec![6, 5], vec![5, 4, 3, 2, 1]];
+        assert_eq!(
+            sum_fixed(&rules, &updates, MiddlePolicy::Error),
+            Err(MiddleError::Cycle {
+                update: 1,
+                err: CycleError {
+                    pages: vec![1, 2, 3]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_repair_update() {
+        let (rules, updates) = get_rules_and_updates(include_str!("input_test")).unwrap();
+
+        for update in &updates {
+            let repair = repair_update(update, &rules).unwrap();
+
+            assert!(check_valid_update(&repair.order, &rules));
+            assert_eq!(repair.moved.is_empty(), check_valid_update(update, &rules));
+        }
+
+        // the fixer lets 3 go first, so both 1 and 2 end up behind it
+        let rules = RuleSet::from_iter([Rule(4, 1), Rule(4, 2)]);
+        let update = [1, 2, 3, 4];
+
+        assert_eq!(fix_invalid_update(&update, &rules), Ok(vec![3, 4, 1, 2]));
+        assert_eq!(
+            repair_update(&update, &rules),
+            Ok(Repair {
+                order: vec![4, 1, 2, 3],
+                moved: vec![4]
+            })
+        );
+
+        // 1 and 3 can't both stay: 3 has to come before 2, which has to come before 1
+        let rules = RuleSet::from_iter([Rule(3, 2), Rule(2, 1)]);
+        let repair = repair_update(&[1, 2, 3, 4], &rules).unwrap();
+
+        assert_eq!(repair.moved.len(), 2);
+        assert!(check_valid_update(&repair.order, &rules));
+        assert!(repair_update(&[1, 2], &RuleSet::from_iter([Rule(1, 2), Rule(2, 1)])).is_err());
+    }
+
+    #[test]
+    fn test_repair_update_is_minimal() {
+        fn permutations(pages: &[u64]) -> Vec<Vec<u64>> {
+            if pages.len() <= 1 {
+                return vec![pages.to_vec()];
+            }
+
+            (0..pages.len())
+                .flat_map(|idx| {
+                    let mut rest = pages.to_vec();
+                    let page = rest.remove(idx);
+
+                    permutations(&rest).into_iter().map(move |mut tail| {
+                        tail.insert(0, page);
+                        tail
+                    })
+                })
+                .collect()
+        }
+
+        // pages of `order` also in `update`, in the same order, i.e. those not moved
+        fn kept(update: &[u64], order: &[u64]) -> usize {
+            let mut longest = vec![vec![0; order.len() + 1]; update.len() + 1];
+
+            for (i, a) in update.iter().enumerate() {
+                for (j, b) in order.iter().enumerate() {
+                    longest[i + 1][j + 1] = if a == b {
+                        longest[i][j] + 1
+                    } else {
+                        longest[i][j + 1].max(longest[i + 1][j])
+                    };
+                }
+            }
+
+            longest[update.len()][order.len()]
+        }
+
+        let mut seed = 7u64;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed >> 33
+        };
+
+        for _ in 0..200 {
+            let pages = [1, 2, 3, 4, 5, 6];
+            let mut rank = pages;
+            rank.sort_by_key(|_| random());
+
+            let rules = (0..pages.len())
+                .flat_map(|i| (i + 1..pages.len()).map(move |j| Rule(rank[i], rank[j])))
+                .filter(|_| random() % 3 == 0)
+                .collect::<RuleSet>();
+            let mut update = pages;
+            update.sort_by_key(|_| random());
+
+            let best = permutations(&update)
+                .into_iter()
+                .filter(|order| check_valid_update(order, &rules))
+                .map(|order| kept(&update, &order))
+                .max()
+                .unwrap();
+            let repair = repair_update(&update, &rules).unwrap();
+
+            assert!(check_valid_update(&repair.order, &rules));
+            assert_eq!(
+                repair.moved.len(),
+                update.len() - best,
+                "{update:?} {rules:?}"
+            );
+            assert_eq!(kept(&update, &repair.order), best);
+        }
+    }
+
+    #[test]
+    fn test_print_queue() {
+        let (rules, updates) = get_rules_and_updates(include_str!("input_test")).unwrap();
+        let mut queue = PrintQueue::new(rules.clone(), updates.clone());
+
+        // update 4 only breaks 97|75
+        assert_eq!(queue.remove(Rule(97, 75)), vec![3]);
+        assert_eq!(queue.remove(Rule(97, 75)), Vec::<usize>::new());
+        assert_eq!(queue.insert(Rule(97, 75)), Ok(vec![3]));
+        assert_eq!(queue.insert(Rule(97, 75)), Ok(Vec::new()));
+
+        // 13 comes after everything, so the other way round closes cycles
+        assert_eq!(
+            queue.insert(Rule(13, 97)),
+            Err(CycleError {
+                pages: vec![13, 97]
+            })
+        );
+        assert_eq!(
+            queue.insert(Rule(13, 47)),
+            Err(CycleError {
+                pages: vec![13, 47]
+            })
+        );
+        assert!(!queue.rules().contains(Rule(13, 97)));
+
+        // rules only matter for pages in the same update
+        let mut queue = PrintQueue::new(
+            RuleSet::from_iter([Rule(1, 2), Rule(2, 3)]),
+            vec![vec![1, 2], vec![2, 3], vec![3, 4]],
+        );
+
+        assert_eq!(queue.insert(Rule(3, 1)), Ok(Vec::new()));
+        assert_eq!(queue.insert(Rule(4, 3)), Ok(vec![2]));
+        assert_eq!(
+            queue.insert(Rule(2, 1)),
+            Err(CycleError { pages: vec![2, 1] })
+        );
+        assert_eq!(queue.insert(Rule(4, 4)), Err(CycleError { pages: vec![4] }));
+        assert_eq!(queue.insert(Rule(5, 5)), Ok(Vec::new()));
+
+        assert_eq!(queue.remove(Rule(4, 3)), vec![2]);
+        assert!(queue.rules().contains(Rule(3, 1)));
+        assert_eq!(queue.updates(), [vec![1, 2], vec![2, 3], vec![3, 4]]);
+
+        // the rest agrees with a queue built from scratch
+        let mut queue = PrintQueue::new(rules.clone(), updates.clone());
+        let mut edited = rules.clone();
+
+        for rule in [Rule(47, 53), Rule(75, 13), Rule(61, 29)] {
+            queue.remove(rule);
+            edited.remove(rule);
+        }
+
+        for (idx, update) in updates.iter().enumerate() {
+            assert_eq!(queue.valid[idx], check_valid_update(update, &edited));
+        }
+    }
+
+    #[test]
+    fn test_get_rules_and_updates() {
+        let input = include_str!("input_test");
+        let (rules, updates) = get_rules_and_updates(input).unwrap();
+
+        let messy = format!("\n\n{}\n\n", input.replace('\n', "  \r\n"))
+            .replace("\r\n  \r\n", "\r\n\r\n \r\n\t\r\n");
+        assert_eq!(get_rules_and_updates(&messy), Ok((rules, updates)));
+
+        let error = |input: &str| get_rules_and_updates(input).unwrap_err();
+
+        assert_eq!(error(""), InputError::MissingRules);
+        assert_eq!(error("1|2\n2|3\n"), InputError::MissingUpdates);
+        assert_eq!(error("\n1,2,3\n"), InputError::MissingRules);
+        assert_eq!(error("1|2\n\n1,2\n\n3|4"), InputError::ExtraSection(5));
+        assert_eq!(
+            error("1|2\n1|2|3\n\n1,2"),
+            InputError::InvalidRule {
+                line: 2,
+                text: "1|2|3".to_string()
+            }
+        );
+        assert_eq!(
+            error("1|2\n1-3\n\n1,2"),
+            InputError::InvalidRule {
+                line: 2,
+                text: "1-3".to_string()
+            }
+        );
+        assert_eq!(
+            error("1|2\n\n1,2\n1,,2").to_string(),
+            "line 4: expected pages like 75,47,61, found \"1,,2\""
+        );
+    }
+
+    #[test]
+    fn test_update_issues() {
+        let updates = [vec![1, 2, 3], vec![1, 2], vec![4, 5, 4], vec![6, 6]];
+
+        assert_eq!(
+            update_issues(&updates),
+            vec![
+                UpdateIssue::EvenLength { update: 1, len: 2 },
+                UpdateIssue::DuplicatePage { update: 2, page: 4 },
+                UpdateIssue::DuplicatePage { update: 3, page: 6 },
+                UpdateIssue::EvenLength { update: 3, len: 2 },
+            ]
+        );
+        assert_eq!(
+            update_issues(&updates)[0].to_string(),
+            "update 2 has 2 pages and no single middle one"
+        );
+    }
+
+    #[test]
+    fn test_middle_policy() {
+        let rules = RuleSet::from_iter([Rule(1, 2)]);
+        let updates = [vec![1, 2, 3], vec![4, 5], vec![2, 1, 6, 7], vec![2, 1, 1]];
+        let sum = |policy| {
+            let valid = sum_valid(&rules, &updates, policy);
+            let fixed = sum_fixed(&rules, &updates, policy);
+            (valid, fixed)
+        };
+
+        assert_eq!(
+            sum(MiddlePolicy::Error),
+            (
+                Err(MiddleError::NoMiddle { updates: vec![1] }),
+                Err(MiddleError::NoMiddle {
+                    updates: vec![2, 3]
+                })
+            )
+        );
+        assert_eq!(
+            sum(MiddlePolicy::Lower),
+            (
+                Ok(MiddleSum {
+                    sum: 2 + 4,
+                    even: vec![1]
+                }),
+                Ok(MiddleSum {
+                    sum: 2 + 1,
+                    even: vec![2, 3]
+                })
+            )
+        );
+        assert_eq!(
+            sum(MiddlePolicy::Upper).1,
+            Ok(MiddleSum {
+                sum: 6 + 2,
+                even: vec![2, 3]
+            })
+        );
+        assert_eq!(
+            sum(MiddlePolicy::Skip).0,
+            Ok(MiddleSum {
+                sum: 2,
+                even: vec![1]
+            })
+        );
+
+        let input = "1|2\n\n1,2,3\n4,5\n2,1,6,7\n";
+        assert_eq!(
+            first_part(input),
+            Err(MiddleError::NoMiddle { updates: vec![1] })
+        );
+        assert_eq!(
+            first_part_with(input, MiddlePolicy::Upper).map(|sum| sum.sum),
+            Ok(2 + 5)
+        );
+        assert_eq!(
+            second_part_with(input, MiddlePolicy::Lower).map(|sum| sum.sum),
+            Ok(2)
+        );
+
+        assert_eq!(MiddlePolicy::Skip.middle(&[]), Some(None));
+        assert_eq!(MiddlePolicy::Lower.middle(&[]), None);
+        assert_eq!(MiddlePolicy::Error.middle(&[1, 2, 3]), Some(Some(2)));
+        assert_eq!(
+            MiddleError::NoMiddle {
+                updates: vec![0, 3]
+            }
+            .to_string(),
+            "no single middle page in updates 1, 4, choose one with a middle page policy"
+        );
+    }
+
+    #[test]
+    fn test_rule_set() {
+        let mut rules = RuleSet::from_iter([Rule(1, 2), Rule(1, 3), Rule(3, 2)]);
+
+        assert!(!rules.insert(Rule(1, 2)));
+
+        let mut after = rules.after(1).collect::<Vec<_>>();
+        after.sort();
+        assert_eq!(after, vec![2, 3]);
+
+        let mut before = rules.before(2).collect::<Vec<_>>();
+        before.sort();
+        assert_eq!(before, vec![1, 3]);
+
+        assert_eq!(rules.after(2).count(), 0);
+        assert_eq!(rules.before(4).count(), 0);
+
+        assert!(check_valid_update(&[1, 3, 2], &rules));
+        assert!(check_valid_update(&[4, 3, 5], &rules));
+        assert!(!check_valid_update(&[3, 1], &rules));
+    }
+
+    #[test]
+    fn test_violations() {
+        let input = include_str!("input_test");
+        let (rules, updates) = get_rules_and_updates(input).unwrap();
+
+        for update in &updates {
+            assert_eq!(
+                violations(update, &rules).is_empty(),
+                check_valid_update(update, &rules)
+            );
+        }
+
+        assert_eq!(
+            violations(&[97, 13, 75, 29, 47], &rules),
+            vec![
+                Violation {
+                    rule: Rule(75, 13),
+                    first: 2,
+                    second: 1
+                },
+                Violation {
+                    rule: Rule(29, 13),
+                    first: 3,
+                    second: 1
+                },
+                Violation {
+                    rule: Rule(47, 13),
+                    first: 4,
+                    second: 1
+                },
+                Violation {
+                    rule: Rule(47, 29),
+                    first: 4,
+                    second: 3
+                },
+            ]
+        );
+        assert_eq!(
+            violations(&[61, 13, 29], &rules)[0].to_string(),
+            "29 at index 2 comes after 13 at index 1, breaking 29|13"
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let rules = RuleSet::from_iter([Rule(1, 2), Rule(2, 3), Rule(3, 1), Rule(4, 5)]);
+        let updates = [vec![4, 5], vec![5, 4], vec![3, 2, 1]];
+
+        let expected = "\
+update 2: 5,4
+  4 at index 1 comes after 5 at index 0, breaking 4|5
+  fixed: 4,5
+update 3: 3,2,1
+  2 at index 1 comes after 3 at index 0, breaking 2|3
+  1 at index 2 comes after 2 at index 1, breaking 1|2
+  not fixable: rules form a cycle: 1 -> 2 -> 3 -> 1
+3 updates, 2 invalid
+";
+        assert_eq!(report(&rules, &updates, false), expected);
+
+        let expected = "\
+update 2: 5,4
+  4 at index 1 comes after 5 at index 0, breaking 4|5
+  repaired: 4,5
+  moved: 4
+update 3: 3,2,1
+  2 at index 1 comes after 3 at index 0, breaking 2|3
+  1 at index 2 comes after 2 at index 1, breaking 1|2
+  not repairable: rules form a cycle: 1 -> 2 -> 3 -> 1
+3 updates, 2 invalid
+";
+        assert_eq!(report(&rules, &updates, true), expected);
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let rules = RuleSet::from_iter([
+            Rule(1, 2),
+            Rule(2, 3),
+            Rule(1, 3),
+            Rule(3, 4),
+            Rule(1, 4),
+            Rule(5, 4),
+        ]);
+        let reduction = rules.transitive_reduction().unwrap();
+
+        assert_eq!(
+            reduction.rules(),
+            vec![Rule(1, 2), Rule(2, 3), Rule(3, 4), Rule(5, 4)]
+        );
+        assert_eq!(
+            RuleSet::from_iter([Rule(1, 2), Rule(2, 1)]).transitive_reduction(),
+            Err(CycleError { pages: vec![2, 1] })
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let (rules, _) = get_rules_and_updates(include_str!("input_test")).unwrap();
+
+        assert_eq!(
+            summary(&rules),
+            "7 pages, 21 rules\ntransitive reduction: 6 rules\ntotal order: yes\n"
+        );
+
+        let partial = RuleSet::from_iter([Rule(1, 2), Rule(1, 3)]);
+        assert_eq!(
+            summary(&partial),
+            "3 pages, 2 rules\ntransitive reduction: 2 rules\ntotal order: no\n"
+        );
+
+        // as many rules as a chain, and no page with more than one after it
+        let joined = RuleSet::from_iter([Rule(1, 3), Rule(2, 3)]);
+        assert_eq!(
+            summary(&joined),
+            "3 pages, 2 rules\ntransitive reduction: 2 rules\ntotal order: no\n"
+        );
+
+        let cycle = RuleSet::from_iter([Rule(1, 2), Rule(2, 1)]);
+        assert_eq!(
+            summary(&cycle),
+            "2 pages, 2 rules\nno transitive reduction, rules form a cycle: 2 -> 1 -> 2\ntotal order: no\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let rules = RuleSet::from_iter([Rule(1, 2), Rule(2, 3), Rule(3, 4)]);
+
+        assert_eq!(
+            to_dot(&rules, None),
+            "digraph rules {\n    1;\n    2;\n    3;\n    4;\n    1 -> 2;\n    2 -> 3;\n    3 -> 4;\n}\n"
+        );
+        assert_eq!(
+            to_dot(&rules, Some(&[3, 2, 5])),
+            "digraph rules {\n    2;\n    3;\n    5;\n    2 -> 3 [color=red];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let args = ["--report", "--file", "rules.txt"].map(String::from);
+        let options = Options::from_args(&args).unwrap();
+
+        assert!(options.report && !options.repair);
+        assert_eq!(options.file.as_deref(), Some("rules.txt"));
+
+        let options = Options::from_args(&["--repair".to_string()]).unwrap();
+        assert!(options.report && options.repair);
+
+        let args = ["--insert", "1|2", "--remove", "3|4"].map(String::from);
+        assert_eq!(
+            Options::from_args(&args).unwrap().edits,
+            vec![Edit::Insert(Rule(1, 2)), Edit::Remove(Rule(3, 4))]
+        );
+        assert!(Options::from_args(&["--insert".to_string(), "1-2".to_string()]).is_err());
+
+        let args = ["--middle", "lower"].map(String::from);
+        assert_eq!(
+            Options::from_args(&args).unwrap().middle,
+            MiddlePolicy::Lower
+        );
+        assert_eq!(Options::from_args(&[]).unwrap().middle, MiddlePolicy::Error);
+        assert!(Options::from_args(&["--middle", "first"].map(String::from)).is_err());
+
+        assert!(Options::from_args(&["--file".to_string()]).is_err());
+        assert!(Options::from_args(&["--fix".to_string()]).is_err());
+
+        let args = ["--dot", "--update", "3", "--summary"].map(String::from);
+        let options = Options::from_args(&args).unwrap();
+
+        assert!(options.dot && options.summary && !options.report);
+        assert_eq!(options.update, Some(3));
+
+        assert!(Options::from_args(&["--update".to_string(), "3".to_string()]).is_err());
+        assert!(Options::from_args(&["--dot", "--update", "x"].map(String::from)).is_err());
+    }
+
     #[test]
     fn test_first_part() {
         let input = include_str!("input_test");
-        assert_eq!(first_part(input), 143);
+        assert_eq!(first_part(input), Ok(143));
     }
 
     #[test]
     fn test_second_part() {
         let input = include_str!("input_test");
-        assert_eq!(second_part(input), 123);
+        assert_eq!(second_part(input), Ok(123));
     }
 }
@@ -1,11 +1,20 @@
+use aoc2024::grid::{Coord, Grid, GridError};
+
 fn main() {
     let input = include_str!("input");
-    println!("First part: {}", first_part(input));
+
+    match first_part(input) {
+        Ok(visited) => println!("First part: {visited}"),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
     //println!("Second part: {}", second_part(input));
 }
 
 #[derive(Clone, Copy, PartialEq)]
-enum Block {
+pub enum Block {
     Start,
     Unchecked,
     Checked,
@@ -31,111 +40,100 @@ impl From<char> for Block {
     }
 }
 
+impl Direction {
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Forward => (-1, 0),
+            Direction::Backward => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+}
+
 struct Runner {
-    curr_row: usize,
-    curr_col: usize,
+    curr_position: Coord,
     curr_direction: Direction,
-    grid: Vec<Vec<Block>>,
+    grid: Grid<Block>,
 }
 
 impl Runner {
-    fn new(grid: Vec<Vec<Block>>) -> Self {
-        let starts = grid.iter().enumerate().flat_map(|(row_idx, row)| {
-            if let Some(col_idx) = row.iter().position(|block| block == &Block::Start) {
-                Some((row_idx, col_idx))
-            } else {
-                None
-            }
-        }).collect::<Vec<_>>();
-        let (start_row, start_col) = starts.first().unwrap();
+    fn new(grid: Grid<Block>) -> Self {
+        let start = grid.position(|block| block == &Block::Start).unwrap();
 
         Self {
-            curr_row: *start_row,
-            curr_col: *start_col,
+            curr_position: start,
             curr_direction: Direction::Forward,
-            grid
+            grid,
+        }
     }
 }
-}
 
 impl Iterator for Runner {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let original_row = self.curr_row;
-        let original_col = self.curr_col;
-
-
-        if self.curr_row + 1 >= self.grid.len() || self.curr_col + 1 >= self.grid[0].len() || self.curr_row.checked_sub(1).is_none() || self.curr_col.checked_sub(1).is_none()  {
-            return None
-        }
-
-        match self.curr_direction {
-            Direction::Forward => {
-                self.curr_row -= 1;
-            }
-            Direction::Backward => {
-                self.curr_row += 1;
-            }
-            Direction::Left => {
-                self.curr_col -= 1;
-            }
-            Direction::Right => {
-                self.curr_col += 1;
-            }
-        }
+        let next_position = self
+            .grid
+            .step(self.curr_position, self.curr_direction.offset())?;
 
-        let next_block = self.grid[self.curr_row][self.curr_col];
-
-        match next_block {
+        match self.grid[next_position] {
             Block::Blocked => {
-                self.curr_row = original_row;
-                self.curr_col = original_col;
                 self.curr_direction = match self.curr_direction {
                     Direction::Forward => Direction::Right,
                     Direction::Right => Direction::Backward,
                     Direction::Backward => Direction::Left,
-                    Direction::Left => Direction::Forward
+                    Direction::Left => Direction::Forward,
                 };
 
                 Some(0)
-            },
+            }
             Block::Unchecked => {
-                self.grid[self.curr_row][self.curr_col] = Block::Checked;
+                self.grid[next_position] = Block::Checked;
+                self.curr_position = next_position;
                 Some(1)
-            },
-            _ => Some(0)
+            }
+            _ => {
+                self.curr_position = next_position;
+                Some(0)
+            }
         }
     }
 }
 
-fn build_grid(input: &str) -> Vec<Vec<Block>> {
-    input
-        .lines()
-        .map(|line| line.chars().map(|c| Block::from(c)).collect::<Vec<_>>())
-        .collect::<Vec<_>>()
+pub fn build_grid(input: &str) -> Result<Grid<Block>, GridError> {
+    Grid::parse(input, Block::from)
 }
 
-fn first_part(input: &str) -> u64 {
-    let grid = build_grid(input);
+pub fn first_part(input: &str) -> Result<u64, GridError> {
+    let grid = build_grid(input)?;
     let runner = Runner::new(grid);
     let visited: usize = runner.into_iter().sum();
-    (visited + 1) as u64
+    Ok((visited + 1) as u64)
 }
 
-fn second_part(input: &str) -> u64 {
+#[allow(dead_code)]
+pub fn second_part(_input: &str) -> u64 {
     todo!()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{build_grid, first_part, Runner};
-
+    use super::{first_part, GridError};
 
     #[test]
     fn test_first_part() {
         let input = include_str!("input_test");
         let visited = first_part(input);
-        assert_eq!(visited, 41);
+        assert_eq!(visited, Ok(41));
+
+        assert_eq!(
+            first_part("..\n.^.\n"),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
     }
 }
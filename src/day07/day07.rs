@@ -9,14 +9,14 @@ fn main() {
 enum Operator {
     Add,
     Multiply,
-    Concat
+    Concat,
 }
 
 const OPERATORS: [Operator; 2] = [Operator::Add, Operator::Multiply];
 const EXTENDED_OPERATORS: [Operator; 3] = [Operator::Add, Operator::Multiply, Operator::Concat];
 
 #[derive(Debug)]
-enum Expression {
+pub enum Expression {
     Number(u32),
     Combined(Rc<Expression>, u32),
     Empty,
@@ -37,7 +37,9 @@ impl Expression {
                         let result = match op {
                             Operator::Add => exp_result + *number as u64,
                             Operator::Multiply => exp_result * *number as u64,
-                            Operator::Concat => format!("{exp_result}{number}").parse::<u64>().expect("malformed number")
+                            Operator::Concat => format!("{exp_result}{number}")
+                                .parse::<u64>()
+                                .expect("malformed number"),
                         };
                         results.push(result);
                     }
@@ -48,7 +50,7 @@ impl Expression {
     }
 }
 
-fn first_part(input: &str) -> u64 {
+pub fn first_part(input: &str) -> u64 {
     let expressions = parse(input);
     expressions
         .iter()
@@ -56,13 +58,11 @@ fn first_part(input: &str) -> u64 {
             let results = expression.evaluate(&OPERATORS);
             results.contains(expected)
         })
-        .map(|(num, _)| {
-            num
-        })
+        .map(|(num, _)| num)
         .sum()
 }
 
-fn second_part(input: &str) -> u64 {
+pub fn second_part(input: &str) -> u64 {
     let expressions = parse(input);
     expressions
         .iter()
@@ -70,9 +70,7 @@ fn second_part(input: &str) -> u64 {
             let results = expression.evaluate(&EXTENDED_OPERATORS);
             results.contains(expected)
         })
-        .map(|(num, _)| {
-            num
-        })
+        .map(|(num, _)| num)
         .sum()
 }
 
@@ -86,7 +84,7 @@ impl From<Vec<u32>> for Expression {
     }
 }
 
-fn parse(input: &str) -> Vec<(u64, Expression)> {
+pub fn parse(input: &str) -> Vec<(u64, Expression)> {
     input
         .lines()
         .flat_map(|line| {
@@ -106,7 +104,7 @@ fn parse(input: &str) -> Vec<(u64, Expression)> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{first_part, second_part};
+    use super::{first_part, second_part};
 
     #[test]
     fn test_first_part() {
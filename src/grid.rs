@@ -0,0 +1,607 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A `(row, column)` position in a grid.
+pub type Coord = (usize, usize);
+
+/// Offsets to the four orthogonal neighbours: up, right, down, left.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Offsets to all eight neighbours, clockwise starting upwards.
+pub const ALL_DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+/// What a single cell of the text given to `Grid::parse_with` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cells {
+    /// One Unicode scalar value per cell.
+    Chars,
+    /// One extended grapheme cluster per cell, so e.g. a letter followed by a
+    /// combining accent or a flag emoji is a single cell.
+    Graphemes,
+}
+
+impl Cells {
+    /// The cells of a single line.
+    pub fn split(self, line: &str) -> Vec<&str> {
+        match self {
+            Cells::Chars => line
+                .char_indices()
+                .map(|(idx, c)| &line[idx..idx + c.len_utf8()])
+                .collect(),
+            Cells::Graphemes => line.graphemes(true).collect(),
+        }
+    }
+}
+
+/// What `Grid::parse_with` does with lines of different lengths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ragged<T> {
+    /// Fail with `GridError::Ragged` for the first line not as long as the first one.
+    Reject,
+    /// Fill up every line with the given cell to the length of the longest one.
+    Pad(T),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// A line (1-based) with a different length than the first one.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells, expected {expected} like the first line"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Rectangular grid stored row by row in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from one line of text per row, mapping every char through `cell`.
+    pub fn parse(input: &str, mut cell: impl FnMut(char) -> T) -> Result<Self, GridError> {
+        let mut cells = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+
+        for (idx, line) in input.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut cell));
+            let found = cells.len() - before;
+
+            if idx == 0 {
+                cols = found;
+            } else if found != cols {
+                return Err(GridError::Ragged {
+                    line: idx + 1,
+                    expected: cols,
+                    found,
+                });
+            }
+
+            rows += 1;
+        }
+
+        Ok(Self { cells, rows, cols })
+    }
+
+    /// Like `parse`, but splitting lines into `cells` and handling lines of
+    /// different lengths as `ragged` says.
+    pub fn parse_with<'a>(
+        input: &'a str,
+        cells: Cells,
+        ragged: Ragged<T>,
+        mut cell: impl FnMut(&'a str) -> T,
+    ) -> Result<Self, GridError>
+    where
+        T: Clone,
+    {
+        let lines = input
+            .lines()
+            .map(|line| cells.split(line))
+            .collect::<Vec<_>>();
+        let cols = match ragged {
+            Ragged::Reject => lines.first().map_or(0, Vec::len),
+            Ragged::Pad(_) => lines.iter().map(Vec::len).max().unwrap_or(0),
+        };
+        let mut grid = Vec::with_capacity(lines.len() * cols);
+
+        for (idx, line) in lines.iter().enumerate() {
+            grid.extend(line.iter().map(|text| cell(text)));
+
+            match &ragged {
+                Ragged::Reject if line.len() != cols => {
+                    return Err(GridError::Ragged {
+                        line: idx + 1,
+                        expected: cols,
+                        found: line.len(),
+                    })
+                }
+                Ragged::Reject => {}
+                Ragged::Pad(fill) => {
+                    grid.extend(std::iter::repeat_n(fill.clone(), cols - line.len()))
+                }
+            }
+        }
+
+        Ok(Self {
+            cells: grid,
+            rows: lines.len(),
+            cols,
+        })
+    }
+
+    /// Builds a `rows` x `cols` grid, calling `cell` for every coordinate row by row.
+    pub fn from_fn(rows: usize, cols: usize, mut cell: impl FnMut(Coord) -> T) -> Self {
+        let cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(&mut cell)
+            .collect();
+
+        Self { cells, rows, cols }
+    }
+
+    pub fn cnt_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cnt_columns(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, (row, col): Coord) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.contains(coord)
+            .then(|| &self.cells[coord.0 * self.cols + coord.1])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.contains(coord)
+            .then(|| &mut self.cells[coord.0 * self.cols + coord.1])
+    }
+
+    /// Moves one `offset` away from `coord`, `None` when that leaves the grid.
+    pub fn step(&self, (row, col): Coord, (d_row, d_col): (isize, isize)) -> Option<Coord> {
+        let next = (
+            row.checked_add_signed(d_row)?,
+            col.checked_add_signed(d_col)?,
+        );
+
+        self.contains(next).then_some(next)
+    }
+
+    pub fn neighbours4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        ORTHOGONAL
+            .into_iter()
+            .filter_map(move |offset| self.step(coord, offset))
+    }
+
+    pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        ALL_DIRECTIONS
+            .into_iter()
+            .filter_map(move |offset| self.step(coord, offset))
+    }
+
+    /// All coordinates, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let cols = self.cols;
+
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+    }
+
+    /// Coordinate of the first cell (row by row) matching `predicate`.
+    pub fn position(&self, predicate: impl FnMut(&T) -> bool) -> Option<Coord> {
+        let idx = self.cells.iter().position(predicate)?;
+
+        Some((idx / self.cols, idx % self.cols))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.rows).map(|row| self.row(row))
+    }
+
+    pub fn col(&self, col: usize) -> Line<'_, T> {
+        self.line((0, col), (1, 0))
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = Line<'_, T>> {
+        (0..self.cols).map(|col| self.col(col))
+    }
+
+    /// The cells from `start` onwards, moving by `offset` until leaving the grid.
+    /// Empty if `start` is outside the grid or `offset` is `(0, 0)`, which
+    /// would never leave it.
+    pub fn line(&self, start: Coord, offset: (isize, isize)) -> Line<'_, T> {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        let fits = |pos: isize, step: isize, len: isize| match step {
+            0 => isize::MAX,
+            step if step > 0 => (len - 1 - pos) / step + 1,
+            step => pos / -step + 1,
+        };
+
+        let (next, remaining) = match self.contains(start) && offset != (0, 0) {
+            true => (
+                start.0 * self.cols + start.1,
+                fits(start.0 as isize, offset.0, rows).min(fits(start.1 as isize, offset.1, cols)),
+            ),
+            false => (0, 0),
+        };
+
+        Line {
+            cells: &self.cells,
+            next,
+            stride: offset.0 * cols + offset.1,
+            remaining: remaining as usize,
+        }
+    }
+
+    /// Diagonals going down and to the right, starting along the top row, then
+    /// the left column.
+    pub fn forward_diagonals(&self) -> impl Iterator<Item = Line<'_, T>> {
+        let top = (0..self.cols).map(|col| self.line((0, col), (1, 1)));
+        let side = (1..self.rows).map(|row| self.line((row, 0), (1, 1)));
+
+        top.chain(side)
+    }
+
+    /// Diagonals going down and to the left, starting along the top row from
+    /// the right, then the right column.
+    pub fn backward_diagonals(&self) -> impl Iterator<Item = Line<'_, T>> {
+        let top = (0..self.cols).rev().map(|col| self.line((0, col), (1, -1)));
+        let side = (1..self.rows)
+            .filter_map(|row| Some(self.line((row, self.cols.checked_sub(1)?), (1, -1))));
+
+        top.chain(side)
+    }
+
+    /// Every diagonal of the grid: those of `forward_diagonals` and
+    /// `backward_diagonals` starting in the top row, then the remaining ones.
+    pub fn diagonals(&self) -> impl Iterator<Item = Line<'_, T>> {
+        let top_forward = self.forward_diagonals().take(self.cols);
+        let top_backward = self.backward_diagonals().take(self.cols);
+        let side_forward = self.forward_diagonals().skip(self.cols);
+        let side_backward = self.backward_diagonals().skip(self.cols);
+
+        top_forward
+            .chain(top_backward)
+            .chain(side_forward)
+            .chain(side_backward)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Borrowing view of the `rows` x `cols` area whose top left corner is `top_left`.
+    pub fn window(&self, top_left: Coord, rows: usize, cols: usize) -> Option<Window<'_, T>> {
+        if top_left.0 + rows > self.rows || top_left.1 + cols > self.cols {
+            return None;
+        }
+
+        Some(Window {
+            grid: self,
+            top_left,
+            rows,
+            cols,
+        })
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Copy of the `rows` x `cols` area whose top left corner is `top_left`.
+    pub fn subgrid(&self, top_left: Coord, rows: usize, cols: usize) -> Option<Grid<T>> {
+        if top_left.0 + rows > self.rows || top_left.1 + cols > self.cols {
+            return None;
+        }
+
+        let cells = (top_left.0..top_left.0 + rows)
+            .flat_map(|row| self.row(row)[top_left.1..top_left.1 + cols].iter().cloned())
+            .collect();
+
+        Some(Grid { cells, rows, cols })
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &Self::Output {
+        self.get(coord).expect("coordinate outside of grid")
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut Self::Output {
+        self.get_mut(coord).expect("coordinate outside of grid")
+    }
+}
+
+/// Borrowing view of a rectangular part of a grid, addressed relative to its
+/// top left corner.
+#[derive(Debug)]
+pub struct Window<'g, T> {
+    grid: &'g Grid<T>,
+    top_left: Coord,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Clone for Window<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Window<'_, T> {}
+
+impl<'g, T> Window<'g, T> {
+    pub fn cnt_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cnt_columns(&self) -> usize {
+        self.cols
+    }
+
+    /// Position of the window in the grid.
+    pub fn top_left(&self) -> Coord {
+        self.top_left
+    }
+
+    pub fn get(&self, (row, col): Coord) -> Option<&'g T> {
+        if row < self.rows && col < self.cols {
+            self.grid
+                .get((self.top_left.0 + row, self.top_left.1 + col))
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &'g [T] {
+        assert!(row < self.rows, "row outside of window");
+
+        &self.grid.row(self.top_left.0 + row)[self.top_left.1..self.top_left.1 + self.cols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'g [T]> {
+        let window = *self;
+
+        (0..self.rows).map(move |row| window.row(row))
+    }
+}
+
+impl<T> Index<Coord> for Window<'_, T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &Self::Output {
+        self.get(coord).expect("coordinate outside of window")
+    }
+}
+
+/// Borrowing view of the cells along a straight line through a grid.
+#[derive(Debug, Clone)]
+pub struct Line<'g, T> {
+    cells: &'g [T],
+    /// Flat index of the next cell.
+    next: usize,
+    /// Distance between two cells of the line in `cells`.
+    stride: isize,
+    remaining: usize,
+}
+
+impl<'g, T> Iterator for Line<'g, T> {
+    type Item = &'g T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let cell = &self.cells[self.next];
+        self.remaining -= 1;
+
+        if self.remaining > 0 {
+            self.next = self.next.wrapping_add_signed(self.stride);
+        }
+
+        Some(cell)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Line<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_grid() -> Grid<char> {
+        Grid::parse("ABCDE\n12345\nDEFGH\n67890", |c| c).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = small_grid();
+
+        assert_eq!(grid.cnt_rows(), 4);
+        assert_eq!(grid.cnt_columns(), 5);
+        assert_eq!(grid[(2, 1)], 'E');
+        assert_eq!(grid.get((4, 0)), None);
+        assert_eq!(grid.get((0, 5)), None);
+
+        let digits = Grid::parse("12\n34", |c| c.to_digit(10).unwrap()).unwrap();
+        assert_eq!(digits.rows().collect::<Vec<_>>(), vec![[1, 2], [3, 4]]);
+
+        assert_eq!(
+            Grid::parse("ABC\nDE\nFGH", |c| c),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+
+        let empty = Grid::parse("", |c| c).unwrap();
+        assert_eq!(empty.cnt_rows(), 0);
+        assert_eq!(empty.diagonals().count(), 0);
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let chars = Grid::parse_with("ab\nc\n", Cells::Chars, Ragged::Pad("."), |c| c).unwrap();
+        assert_eq!(
+            chars.rows().collect::<Vec<_>>(),
+            vec![["a", "b"], ["c", "."]]
+        );
+
+        assert_eq!(
+            Grid::parse_with("ab\nc", Cells::Chars, Ragged::Reject, |c| c.to_string()),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+
+        // "e" with a combining acute accent, and a flag made of two regional indicators
+        let text = "e\u{301}x\n\u{1F1E9}\u{1F1EA}y";
+        let graphemes = Grid::parse_with(text, Cells::Graphemes, Ragged::Reject, |c| c).unwrap();
+        assert_eq!(graphemes.cnt_columns(), 2);
+        assert_eq!(graphemes[(0, 0)], "e\u{301}");
+        assert_eq!(graphemes[(1, 0)], "\u{1F1E9}\u{1F1EA}");
+
+        let scalars = Grid::parse_with(text, Cells::Chars, Ragged::Pad(""), |c| c).unwrap();
+        assert_eq!(scalars.cnt_columns(), 3);
+        assert_eq!(scalars[(0, 2)], "x");
+
+        let empty = Grid::parse_with("", Cells::Graphemes, Ragged::Pad(0), |_| 1).unwrap();
+        assert_eq!((empty.cnt_rows(), empty.cnt_columns()), (0, 0));
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let grid = Grid::from_fn(2, 3, |(row, col)| row * 10 + col);
+
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![[0, 1, 2], [10, 11, 12]]
+        );
+        assert_eq!(Grid::from_fn(0, 3, |_| 0).cnt_rows(), 0);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut grid = small_grid();
+
+        *grid.get_mut((1, 1)).unwrap() = 'x';
+        grid[(3, 4)] = 'y';
+
+        assert_eq!(grid.row(1), ['1', 'x', '3', '4', '5']);
+        assert_eq!(grid[(3, 4)], 'y');
+        assert!(grid.get_mut((9, 9)).is_none());
+        assert_eq!(grid.position(|c| *c == 'y'), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = small_grid();
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbours8((3, 4)).collect::<Vec<_>>(),
+            vec![(2, 4), (3, 3), (2, 3)]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(grid.step((0, 0), (-1, 0)), None);
+        assert_eq!(grid.step((0, 0), (1, 1)), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = small_grid();
+
+        let cols = grid
+            .cols()
+            .map(|col| col.copied().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(cols, vec!["A1D6", "B2E7", "C3F8", "D4G9", "E5H0"]);
+
+        let diagonals = grid
+            .diagonals()
+            .map(|diag| diag.copied().collect::<String>())
+            .collect::<Vec<_>>();
+        let expected = vec![
+            "A2F9", "B3G0", "C4H", "D5", "E", "E4F7", "D3E6", "C2D", "B1", "A", "1E8", "D7", "6",
+            "5G8", "H9", "0",
+        ];
+        assert_eq!(diagonals, expected);
+
+        let line = grid.line((3, 4), (-1, -2));
+        assert_eq!(line.len(), 3);
+        assert_eq!(line.copied().collect::<String>(), "0F1");
+        assert_eq!(grid.line((1, 1), (0, 0)).len(), 0);
+        assert_eq!(grid.line((usize::MAX, usize::MAX), (1, 1)).len(), 0);
+
+        let window = grid.window((1, 2), 2, 3).unwrap();
+        assert_eq!(window.top_left(), (1, 2));
+        assert_eq!(window[(1, 0)], 'F');
+        assert_eq!(window.get((0, 3)), None);
+        assert_eq!(
+            window.rows().collect::<Vec<_>>(),
+            vec![['3', '4', '5'], ['F', 'G', 'H']]
+        );
+        assert!(grid.window((0, 4), 1, 2).is_none());
+
+        let window = grid.subgrid((1, 2), 2, 3).unwrap();
+        assert_eq!(
+            window.rows().collect::<Vec<_>>(),
+            vec![['3', '4', '5'], ['F', 'G', 'H']]
+        );
+        assert!(grid.subgrid((3, 0), 2, 1).is_none());
+    }
+}
@@ -0,0 +1,10 @@
+//! Helpers shared between the days.
+
+pub mod answers;
+pub mod bench;
+pub mod client;
+pub mod grid;
+#[cfg(test)]
+mod mock;
+pub mod runner;
+pub mod scaffold;
@@ -0,0 +1,32 @@
+//! A scratch directory for a test, shared by the tests outside the library
+//! through `#[path]`, since they can't reach its `cfg(test)` modules.
+
+use std::path::{Path, PathBuf};
+
+/// A fresh, empty directory named after the test and the process, removed
+/// again on drop.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("aoc2024-{name}-{}", std::process::id()));
+
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,282 @@
+//! Runs the solutions of every day on their input files for the `aoc` command.
+
+use std::{
+    io, panic,
+    path::{Path, PathBuf},
+};
+
+use crate::answers::Answers;
+
+/// Solves one part for an input, rendering the answer as text.
+pub type Solution = fn(&str) -> String;
+
+/// Parses an input the way the parts of a day do, only to time it.
+pub type Parse = fn(&str);
+
+/// The solutions of a day, `None` for parts not solved yet.
+#[derive(Debug, Clone, Copy)]
+pub struct Day {
+    pub number: u8,
+    pub parse: Option<Parse>,
+    pub parts: [Option<Solution>; 2],
+}
+
+impl Day {
+    /// Where the day keeps its code and inputs, e.g. `src/day01` in `root`.
+    pub fn dir(&self, root: &Path) -> PathBuf {
+        root.join("src").join(format!("day{:02}", self.number))
+    }
+
+    /// Names of the input files of the day, i.e. all starting with `input`, sorted.
+    pub fn inputs(&self, root: &Path) -> io::Result<Vec<String>> {
+        let mut inputs = Vec::new();
+
+        for entry in std::fs::read_dir(self.dir(root))? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+
+            if name.starts_with("input") {
+                inputs.push(name);
+            }
+        }
+
+        inputs.sort();
+        Ok(inputs)
+    }
+}
+
+/// Runs a solution, `None` if it panics.
+pub fn run(solution: Solution, input: &str) -> Option<String> {
+    panic::catch_unwind(|| solution(input)).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// A different answer than expected, or none at all.
+    Fail,
+    /// No expected answer, or no input to check it with.
+    Missing,
+}
+
+/// The outcome of checking one part of a day for one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub input: String,
+    pub part: u8,
+    pub expected: Option<String>,
+    /// The answer given, `None` if the part isn't solved, panicked or had no input.
+    pub actual: Option<String>,
+    pub status: Status,
+}
+
+/// Runs both parts of every day on each of its input files as well as on
+/// every input with an expected answer, ordered by day, input and part.
+pub fn verify(days: &[Day], answers: &Answers, root: &Path) -> io::Result<Vec<Check>> {
+    let mut checks = Vec::new();
+
+    for day in days {
+        let mut inputs = day.inputs(root)?;
+        inputs.extend(
+            answers
+                .iter()
+                .filter(|(key, _)| key.day == day.number)
+                .map(|(key, _)| key.input.clone()),
+        );
+        inputs.sort();
+        inputs.dedup();
+
+        for input in inputs {
+            let text = std::fs::read_to_string(day.dir(root).join(&input)).ok();
+
+            for (part, solution) in (1..).zip(day.parts) {
+                let expected = answers.get(day.number, &input, part).map(str::to_string);
+                let actual = match (&text, solution) {
+                    (Some(text), Some(solution)) => run(solution, text),
+                    _ => None,
+                };
+                let status = match (&expected, &actual) {
+                    (None, _) => Status::Missing,
+                    (Some(_), _) if text.is_none() => Status::Missing,
+                    (Some(expected), Some(actual)) if expected == actual => Status::Pass,
+                    (Some(_), _) => Status::Fail,
+                };
+
+                checks.push(Check {
+                    day: day.number,
+                    input: input.clone(),
+                    part,
+                    expected,
+                    actual,
+                    status,
+                });
+            }
+        }
+    }
+
+    Ok(checks)
+}
+
+/// Lays the checks out as a table with a summary line below.
+pub fn table(checks: &[Check]) -> String {
+    let rows = checks
+        .iter()
+        .map(|check| {
+            let status = match check.status {
+                Status::Pass => "pass",
+                Status::Fail => "FAIL",
+                Status::Missing => "missing",
+            };
+
+            [
+                check.day.to_string(),
+                check.part.to_string(),
+                check.input.clone(),
+                check.expected.clone().unwrap_or("-".to_string()),
+                check.actual.clone().unwrap_or("-".to_string()),
+                status.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let header = ["day", "part", "input", "expected", "actual", "status"].map(String::from);
+    let mut widths = header.clone().map(|title| title.len());
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut out = String::new();
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells = row
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(idx, (cell, width))| match idx {
+                0 | 1 => format!("{cell:>width$}"),
+                _ => format!("{cell:<width$}"),
+            })
+            .collect::<Vec<_>>();
+
+        out += cells.join("  ").trim_end();
+        out.push('\n');
+    }
+
+    let count = |status| checks.iter().filter(|check| check.status == status).count();
+    out += &format!(
+        "{} passed, {} failed, {} missing\n",
+        count(Status::Pass),
+        count(Status::Fail),
+        count(Status::Missing)
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::TempDir;
+
+    fn sum(input: &str) -> String {
+        input
+            .split_whitespace()
+            .map(|n| n.parse::<u64>().unwrap())
+            .sum::<u64>()
+            .to_string()
+    }
+
+    fn count(input: &str) -> String {
+        input.split_whitespace().count().to_string()
+    }
+
+    /// A fresh directory with `src/day01/input` and `src/day01/input_test`.
+    fn root(name: &str) -> TempDir {
+        let root = TempDir::new(name);
+        let dir = root.path().join("src").join("day01");
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("input"), "1 2 3 4").unwrap();
+        std::fs::write(dir.join("input_test"), "1 x").unwrap();
+        std::fs::write(dir.join("day01.rs"), "").unwrap();
+
+        root
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = root("verify");
+        let root = dir.path();
+        let days = [Day {
+            number: 1,
+            parse: None,
+            parts: [Some(sum), Some(count)],
+        }];
+        let answers = Answers::parse(
+            "[day01.input]\npart1 = 10\npart2 = 5\n[day01.input_test]\npart1 = 1\n[day01.input_big]\npart1 = 1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            days[0].inputs(root).unwrap(),
+            vec!["input".to_string(), "input_test".to_string()]
+        );
+
+        let checks = verify(&days, &answers, root).unwrap();
+        let summary = checks
+            .iter()
+            .map(|check| (check.input.as_str(), check.part, check.status))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("input", 1, Status::Pass),
+                ("input", 2, Status::Fail),
+                ("input_big", 1, Status::Missing),
+                ("input_big", 2, Status::Missing),
+                ("input_test", 1, Status::Fail),
+                ("input_test", 2, Status::Missing),
+            ]
+        );
+
+        // the sum panics on "x"
+        assert_eq!(checks[4].actual, None);
+        assert_eq!(checks[5].actual.as_deref(), Some("2"));
+
+        let unsolved = [Day {
+            number: 1,
+            parse: None,
+            parts: [None, None],
+        }];
+        let checks = verify(&unsolved, &answers, root).unwrap();
+        assert_eq!(checks[0].status, Status::Fail);
+    }
+
+    #[test]
+    fn test_table() {
+        let check = |part, expected: Option<&str>, actual: Option<&str>, status| Check {
+            day: 7,
+            input: "input".to_string(),
+            part,
+            expected: expected.map(str::to_string),
+            actual: actual.map(str::to_string),
+            status,
+        };
+        let checks = [
+            check(1, Some("3749"), Some("3749"), Status::Pass),
+            check(2, None, Some("11387"), Status::Missing),
+        ];
+
+        let expected = "\
+day  part  input  expected  actual  status
+  7     1  input  3749      3749    pass
+  7     2  input  -         11387   missing
+1 passed, 0 failed, 1 missing
+";
+        assert_eq!(table(&checks), expected);
+    }
+}
@@ -0,0 +1,326 @@
+//! Sets up a new day for `aoc new`: the code from a template, and the day
+//! added as a binary to `Cargo.toml` as well as to the `aoc` command.
+
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScaffoldError {
+    /// Not one of the 25 days.
+    InvalidDay(u8),
+    /// The day is there already, in the named file.
+    Exists { day: u8, file: &'static str },
+    /// No other day to add it next to in the named file.
+    NoDays { file: &'static str },
+}
+
+impl Display for ScaffoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaffoldError::InvalidDay(day) => write!(f, "there's no day {day}"),
+            ScaffoldError::Exists { day, file } => write!(f, "day {day} is in {file} already"),
+            ScaffoldError::NoDays { file } => write!(f, "no days in {file} to add it to"),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {}
+
+pub fn check_day(day: u8) -> Result<(), ScaffoldError> {
+    match (1..=25).contains(&day) {
+        true => Ok(()),
+        false => Err(ScaffoldError::InvalidDay(day)),
+    }
+}
+
+/// The code of a new day, its parts and their tests left to do.
+pub fn template() -> &'static str {
+    r#"fn main() {
+    let input = include_str!("input");
+    println!("First part: {}", first_part(input));
+    println!("Second part: {}", second_part(input));
+}
+
+pub fn first_part(_input: &str) -> u64 {
+    todo!()
+}
+
+pub fn second_part(_input: &str) -> u64 {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_part, second_part};
+
+    /// Stands in for the answers to the sample until they're filled in.
+    const TODO: u64 = u64::MAX;
+
+    #[test]
+    fn test_first_part() {
+        let input = include_str!("input_test");
+        assert_eq!(first_part(input), TODO);
+    }
+
+    #[test]
+    fn test_second_part() {
+        let input = include_str!("input_test");
+        assert_eq!(second_part(input), TODO);
+    }
+}
+"#
+}
+
+/// The line a new `day` goes at among the `days`, each given with the index
+/// of its first line and the index past its last: after the last day before it.
+fn position(
+    days: &[(u8, usize, usize)],
+    day: u8,
+    file: &'static str,
+) -> Result<usize, ScaffoldError> {
+    if days.iter().any(|&(number, _, _)| number == day) {
+        return Err(ScaffoldError::Exists { day, file });
+    }
+
+    let first = days.first().ok_or(ScaffoldError::NoDays { file })?;
+
+    Ok(days
+        .iter()
+        .filter(|&&(number, _, _)| number < day)
+        .map(|&(_, _, end)| end)
+        .max()
+        .unwrap_or(first.1))
+}
+
+/// Inserts `lines` at line `at` of `text`.
+fn insert(text: &str, at: usize, lines: &str) -> String {
+    let mut out = text.lines().take(at).collect::<Vec<_>>();
+    out.extend(lines.lines());
+    out.extend(text.lines().skip(at));
+
+    out.join("\n") + "\n"
+}
+
+/// Adds a `[[bin]]` for the `day` to the manifest, in order with the others.
+pub fn add_bin(manifest: &str, day: u8) -> Result<String, ScaffoldError> {
+    const FILE: &str = "Cargo.toml";
+    let lines = manifest.lines().collect::<Vec<_>>();
+    let mut days = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(number) = line
+            .strip_prefix("name = \"day")
+            .and_then(|rest| rest.strip_suffix('"'))
+            .and_then(|number| number.parse::<u8>().ok())
+        else {
+            continue;
+        };
+
+        if idx == 0 || lines[idx - 1] != "[[bin]]" {
+            continue;
+        }
+
+        let end = (idx..lines.len())
+            .find(|&end| lines[end].trim().is_empty() || lines[end].starts_with('['))
+            .unwrap_or(lines.len());
+        let end = match end < lines.len() && lines[end].trim().is_empty() {
+            true => end + 1,
+            false => end,
+        };
+
+        days.push((number, idx - 1, end));
+    }
+
+    let at = position(&days, day, FILE)?;
+    let bin =
+        format!("[[bin]]\nname = \"day{day:02}\"\npath = \"src/day{day:02}/day{day:02}.rs\"\n");
+    let blank = match lines.get(at.wrapping_sub(1)) {
+        Some(line) if !line.trim().is_empty() => "\n",
+        _ => "",
+    };
+    let after = match lines.get(at) {
+        Some(line) if !line.trim().is_empty() => "\n",
+        _ => "",
+    };
+
+    Ok(insert(manifest, at, &format!("{blank}{bin}{after}")))
+}
+
+/// Adds the `day` to the source of the `aoc` command: its module and its
+/// entry in `DAYS`, both in order with the others.
+pub fn add_to_aoc(source: &str, day: u8) -> Result<String, ScaffoldError> {
+    const FILE: &str = "src/aoc/aoc.rs";
+    let lines = source.lines().collect::<Vec<_>>();
+
+    // each module is declared on three lines, `mod dayNN;` the last of them
+    let modules = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let number = line.strip_prefix("mod day")?.strip_suffix(';')?;
+            Some((number.parse().ok()?, idx.checked_sub(2)?, idx + 1))
+        })
+        .collect::<Vec<_>>();
+
+    // each entry runs from `    Day {` up to `    },`
+    let entries = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| **line == "    Day {")
+        .filter_map(|(start, _)| {
+            let end = (start..lines.len()).find(|&end| lines[end] == "    },")? + 1;
+            let number = lines[start..end].iter().find_map(|line| {
+                line.trim()
+                    .strip_prefix("number: ")?
+                    .strip_suffix(',')?
+                    .parse()
+                    .ok()
+            })?;
+
+            Some((number, start, end))
+        })
+        .collect::<Vec<_>>();
+
+    let module = format!(
+        "#[allow(dead_code)]\n#[path = \"../day{day:02}/day{day:02}.rs\"]\nmod day{day:02};\n"
+    );
+    let entry = format!(
+        "    Day {{
+        number: {day},
+        parse: None,
+        parts: [
+            Some(|input| day{day:02}::first_part(input).to_string()),
+            Some(|input| day{day:02}::second_part(input).to_string()),
+        ],
+    }},
+"
+    );
+
+    // the entries come after the modules, so they go in first
+    let source = insert(source, position(&entries, day, FILE)?, &entry);
+    Ok(insert(&source, position(&modules, day, FILE)?, &module))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[package]
+name = "aoc2024"
+
+[[bin]]
+name = "aoc"
+path = "src/aoc/aoc.rs"
+
+[[bin]]
+name = "day01"
+path = "src/day01/day01.rs"
+
+[[bin]]
+name = "day03"
+path = "src/day03/day03.rs"
+
+[dependencies]
+ureq = "3"
+"#;
+
+    const AOC: &str = r#"use aoc2024::runner::Day;
+
+#[allow(dead_code)]
+#[path = "../day01/day01.rs"]
+mod day01;
+#[allow(dead_code)]
+#[path = "../day03/day03.rs"]
+mod day03;
+
+const DAYS: &[Day] = &[
+    Day {
+        number: 1,
+        parse: None,
+        parts: [None, None],
+    },
+    Day {
+        number: 3,
+        parse: None,
+        parts: [None, None],
+    },
+];
+"#;
+
+    #[test]
+    fn test_add_bin() {
+        let manifest = add_bin(MANIFEST, 2).unwrap();
+        assert!(manifest.contains(
+            "name = \"day01\"\npath = \"src/day01/day01.rs\"\n\n[[bin]]\nname = \"day02\"\npath = \"src/day02/day02.rs\"\n\n[[bin]]\nname = \"day03\""
+        ));
+
+        let manifest = add_bin(&manifest, 4).unwrap();
+        assert!(manifest.contains(
+            "path = \"src/day03/day03.rs\"\n\n[[bin]]\nname = \"day04\"\npath = \"src/day04/day04.rs\"\n\n[dependencies]\n"
+        ));
+
+        assert_eq!(
+            add_bin(&manifest, 3),
+            Err(ScaffoldError::Exists {
+                day: 3,
+                file: "Cargo.toml"
+            })
+        );
+        assert_eq!(
+            add_bin("[package]\nname = \"aoc2024\"\n", 1),
+            Err(ScaffoldError::NoDays { file: "Cargo.toml" })
+        );
+    }
+
+    #[test]
+    fn test_add_to_aoc() {
+        let source = add_to_aoc(AOC, 2).unwrap();
+        let expected = r#"use aoc2024::runner::Day;
+
+#[allow(dead_code)]
+#[path = "../day01/day01.rs"]
+mod day01;
+#[allow(dead_code)]
+#[path = "../day02/day02.rs"]
+mod day02;
+#[allow(dead_code)]
+#[path = "../day03/day03.rs"]
+mod day03;
+
+const DAYS: &[Day] = &[
+    Day {
+        number: 1,
+        parse: None,
+        parts: [None, None],
+    },
+    Day {
+        number: 2,
+        parse: None,
+        parts: [
+            Some(|input| day02::first_part(input).to_string()),
+            Some(|input| day02::second_part(input).to_string()),
+        ],
+    },
+    Day {
+        number: 3,
+        parse: None,
+        parts: [None, None],
+    },
+];
+"#;
+        assert_eq!(source, expected);
+
+        let source = add_to_aoc(&source, 25).unwrap();
+        assert!(source.contains("mod day03;\n#[allow(dead_code)]\n#[path = \"../day25/day25.rs\"]\nmod day25;\n\nconst DAYS"));
+        assert!(
+            source.contains("day25::second_part(input).to_string()),\n        ],\n    },\n];\n")
+        );
+
+        assert_eq!(
+            add_to_aoc(&source, 1),
+            Err(ScaffoldError::Exists {
+                day: 1,
+                file: "src/aoc/aoc.rs"
+            })
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Measures stages with the counting allocator installed, as `aoc bench` does.
+//!
+//! The counter is process-wide, so this is the only test here: another one
+//! running alongside would be counted as well.
+
+use std::hint::black_box;
+
+use aoc2024::bench::{measure, Allocations, Counting};
+
+#[global_allocator]
+static COUNTER: Counting = Counting::new();
+
+#[test]
+fn test_measure_counts_only_the_stage() {
+    for runs in [1, 3] {
+        let (_, allocations) = measure(|| {}, runs, &COUNTER).unwrap();
+        assert_eq!(allocations, Allocations::default());
+
+        let (_, allocations) = measure(|| drop(black_box(vec![0_u8; 64])), runs, &COUNTER).unwrap();
+        assert_eq!(
+            allocations,
+            Allocations {
+                count: 1,
+                reallocations: 0,
+                bytes: 64
+            }
+        );
+    }
+
+    // growing a vector counts what it ends up with, not every size on the way
+    let grow = || {
+        let mut numbers = Vec::new();
+
+        for number in 0..100_u64 {
+            numbers.push(black_box(number));
+        }
+
+        numbers.capacity()
+    };
+    let capacity = grow() as u64;
+    let (_, allocations) = measure(
+        || {
+            black_box(grow());
+        },
+        1,
+        &COUNTER,
+    )
+    .unwrap();
+
+    assert_eq!(allocations.count, 1);
+    assert!(allocations.reallocations > 0);
+    assert_eq!(allocations.bytes, capacity * 8);
+}
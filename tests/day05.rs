@@ -0,0 +1,48 @@
+//! Runs the day05 binary the way it's used from the command line.
+
+use std::process::Command;
+
+#[path = "../src/mock/temp_dir.rs"]
+mod temp_dir;
+
+use temp_dir::TempDir;
+
+/// Runs day05 with `args` on an input file of its own, giving the exit code,
+/// stdout and stderr.
+fn run(name: &str, input: &str, args: &[&str]) -> (Option<i32>, String, String) {
+    let dir = TempDir::new(&format!("day05-{name}"));
+    let path = dir.path().join("input");
+    std::fs::write(&path, input).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_day05"))
+        .arg("--file")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_cyclic_rules() {
+    let input = "1|2\n2|3\n3|1\n4|5\n\n1,2,3\n5,4,6\n";
+
+    let (code, stdout, stderr) = run("cycle", input, &[]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "First part: 0\n");
+    assert_eq!(
+        stderr,
+        "Second part: update 1 is not fixable, rules form a cycle: 2 -> 3 -> 1 -> 2\n"
+    );
+
+    // the report goes on past the update
+    let (code, stdout, _) = run("cycle-report", input, &["--report"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("  not fixable: rules form a cycle: 2 -> 3 -> 1 -> 2\n"));
+    assert!(stdout.contains("  fixed: 4,5,6\n"));
+}